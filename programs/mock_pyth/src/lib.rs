@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+// The test validator loads this program at the address of the Pyth devnet
+// oracle, so it owns the fixture price account and can publish fresh prices
+// to it. Only used by the tests.
+declare_id!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
+
+// Byte offsets into a Pyth v2 price account
+const TIMESTAMP: usize = 96;
+const AGG_PRICE: usize = 208;
+const AGG_CONF: usize = 216;
+const AGG_STATUS: usize = 224;
+
+// PriceStatus::Trading, the only status whose aggregate price is read
+const TRADING: u32 = 1;
+
+#[program]
+pub mod mock_pyth {
+    use super::*;
+
+    pub fn set_price(ctx: Context<SetPrice>, price: i64, conf: u64) -> Result<()> {
+        let mut data = ctx.accounts.price_feed.try_borrow_mut_data()?;
+
+        data[TIMESTAMP..TIMESTAMP + 8].copy_from_slice(&Clock::get()?.unix_timestamp.to_le_bytes());
+        data[AGG_PRICE..AGG_PRICE + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_CONF..AGG_CONF + 8].copy_from_slice(&conf.to_le_bytes());
+        data[AGG_STATUS..AGG_STATUS + 4].copy_from_slice(&TRADING.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    /// CHECK: a Pyth price account, which only this program can write
+    #[account(mut)]
+    pub price_feed: UncheckedAccount<'info>,
+}
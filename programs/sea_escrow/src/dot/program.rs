@@ -2,15 +2,161 @@
 #![allow(unused_variables)]
 #![allow(unused_mut)]
 use crate::{id, seahorse_util::*};
-use anchor_lang::{prelude::*, solana_program};
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_lang::{prelude::*, solana_program, system_program};
+use anchor_spl::token_interface::{
+    self,
+    spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::{
+                instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig,
+            },
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+    },
+    Mint, TokenAccount, TokenInterface,
+};
 use std::{cell::RefCell, rc::Rc};
 
+#[account]
+#[derive(Debug)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl<'info, 'entrypoint> Config {
+    pub fn load(
+        account: &'entrypoint mut Box<Account<'info, Self>>,
+        programs_map: &'entrypoint ProgramsMap<'info>,
+    ) -> Mutable<LoadedConfig<'info, 'entrypoint>> {
+        let admin = account.admin.clone();
+        let pending_admin = account.pending_admin.clone();
+        let fee_bps = account.fee_bps;
+        let treasury = account.treasury.clone();
+        let paused = account.paused;
+        let bump = account.bump;
+
+        Mutable::new(LoadedConfig {
+            __account__: account,
+            __programs__: programs_map,
+            admin,
+            pending_admin,
+            fee_bps,
+            treasury,
+            paused,
+            bump,
+        })
+    }
+
+    pub fn store(loaded: Mutable<LoadedConfig>) {
+        let mut loaded = loaded.borrow_mut();
+        let admin = loaded.admin.clone();
+
+        loaded.__account__.admin = admin;
+
+        let pending_admin = loaded.pending_admin.clone();
+
+        loaded.__account__.pending_admin = pending_admin;
+
+        let fee_bps = loaded.fee_bps;
+
+        loaded.__account__.fee_bps = fee_bps;
+
+        let treasury = loaded.treasury.clone();
+
+        loaded.__account__.treasury = treasury;
+
+        let paused = loaded.paused;
+
+        loaded.__account__.paused = paused;
+
+        let bump = loaded.bump;
+
+        loaded.__account__.bump = bump;
+    }
+}
+
+#[derive(Debug)]
+pub struct LoadedConfig<'info, 'entrypoint> {
+    pub __account__: &'entrypoint mut Box<Account<'info, Config>>,
+    pub __programs__: &'entrypoint ProgramsMap<'info>,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Debug)]
+pub struct Contribution {
+    pub order: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl<'info, 'entrypoint> Contribution {
+    pub fn load(
+        account: &'entrypoint mut Box<Account<'info, Self>>,
+        programs_map: &'entrypoint ProgramsMap<'info>,
+    ) -> Mutable<LoadedContribution<'info, 'entrypoint>> {
+        let order = account.order.clone();
+        let contributor = account.contributor.clone();
+        let amount = account.amount;
+        let bump = account.bump;
+
+        Mutable::new(LoadedContribution {
+            __account__: account,
+            __programs__: programs_map,
+            order,
+            contributor,
+            amount,
+            bump,
+        })
+    }
+
+    pub fn store(loaded: Mutable<LoadedContribution>) {
+        let mut loaded = loaded.borrow_mut();
+        let order = loaded.order.clone();
+
+        loaded.__account__.order = order;
+
+        let contributor = loaded.contributor.clone();
+
+        loaded.__account__.contributor = contributor;
+
+        let amount = loaded.amount;
+
+        loaded.__account__.amount = amount;
+
+        let bump = loaded.bump;
+
+        loaded.__account__.bump = bump;
+    }
+}
+
+#[derive(Debug)]
+pub struct LoadedContribution<'info, 'entrypoint> {
+    pub __account__: &'entrypoint mut Box<Account<'info, Contribution>>,
+    pub __programs__: &'entrypoint ProgramsMap<'info>,
+    pub order: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
 #[account]
 #[derive(Debug)]
 pub struct EscrowOrder {
     pub seller: Pubkey,
-    pub order_id: u16,
+    pub order_id: u64,
     pub bump: u8,
     pub seller_token_account: Pubkey,
     pub referee: Pubkey,
@@ -20,6 +166,27 @@ pub struct EscrowOrder {
     pub amount: u64,
     pub vault: Pubkey,
     pub state: OrderState,
+    pub disputer: Pubkey,
+    pub commission: u16,
+    pub release_window: i64,
+    pub release_deadline: i64,
+    pub dispute_window: i64,
+    pub dispute_deadline: i64,
+    pub dispute_fallback: DisputeFallback,
+    pub designated_buyer: Option<Pubkey>,
+    pub deposited_amount: u64,
+    pub mode: PaymentMode,
+    pub usd_price: Option<UsdPrice>,
+    pub milestones: [u64; 8],
+    pub milestone_count: u8,
+    pub released_milestones: u8,
+    pub ruling: Option<u16>,
+    pub protocol_fee_bps: u16,
+    pub bond: u64,
+    pub item_mint: Pubkey,
+    pub item_vault: Pubkey,
+    pub item_amount: u64,
+    pub funding_deadline: i64,
 }
 
 impl<'info, 'entrypoint> EscrowOrder {
@@ -38,6 +205,27 @@ impl<'info, 'entrypoint> EscrowOrder {
         let amount = account.amount;
         let vault = account.vault.clone();
         let state = account.state.clone();
+        let disputer = account.disputer.clone();
+        let commission = account.commission;
+        let release_window = account.release_window;
+        let release_deadline = account.release_deadline;
+        let dispute_window = account.dispute_window;
+        let dispute_deadline = account.dispute_deadline;
+        let dispute_fallback = account.dispute_fallback.clone();
+        let designated_buyer = account.designated_buyer.clone();
+        let deposited_amount = account.deposited_amount;
+        let mode = account.mode.clone();
+        let usd_price = account.usd_price.clone();
+        let milestones = account.milestones;
+        let milestone_count = account.milestone_count;
+        let released_milestones = account.released_milestones;
+        let ruling = account.ruling.clone();
+        let protocol_fee_bps = account.protocol_fee_bps;
+        let bond = account.bond;
+        let item_mint = account.item_mint.clone();
+        let item_vault = account.item_vault.clone();
+        let item_amount = account.item_amount;
+        let funding_deadline = account.funding_deadline;
 
         Mutable::new(LoadedEscrowOrder {
             __account__: account,
@@ -53,6 +241,27 @@ impl<'info, 'entrypoint> EscrowOrder {
             amount,
             vault,
             state,
+            disputer,
+            commission,
+            release_window,
+            release_deadline,
+            dispute_window,
+            dispute_deadline,
+            dispute_fallback,
+            designated_buyer,
+            deposited_amount,
+            mode,
+            usd_price,
+            milestones,
+            milestone_count,
+            released_milestones,
+            ruling,
+            protocol_fee_bps,
+            bond,
+            item_mint,
+            item_vault,
+            item_amount,
+            funding_deadline,
         })
     }
 
@@ -101,6 +310,90 @@ impl<'info, 'entrypoint> EscrowOrder {
         let state = loaded.state.clone();
 
         loaded.__account__.state = state;
+
+        let disputer = loaded.disputer.clone();
+
+        loaded.__account__.disputer = disputer;
+
+        let commission = loaded.commission;
+
+        loaded.__account__.commission = commission;
+
+        let release_window = loaded.release_window;
+
+        loaded.__account__.release_window = release_window;
+
+        let release_deadline = loaded.release_deadline;
+
+        loaded.__account__.release_deadline = release_deadline;
+
+        let dispute_window = loaded.dispute_window;
+
+        loaded.__account__.dispute_window = dispute_window;
+
+        let dispute_deadline = loaded.dispute_deadline;
+
+        loaded.__account__.dispute_deadline = dispute_deadline;
+
+        let dispute_fallback = loaded.dispute_fallback.clone();
+
+        loaded.__account__.dispute_fallback = dispute_fallback;
+
+        let designated_buyer = loaded.designated_buyer.clone();
+
+        loaded.__account__.designated_buyer = designated_buyer;
+
+        let deposited_amount = loaded.deposited_amount;
+
+        loaded.__account__.deposited_amount = deposited_amount;
+
+        let mode = loaded.mode.clone();
+
+        loaded.__account__.mode = mode;
+
+        let usd_price = loaded.usd_price.clone();
+
+        loaded.__account__.usd_price = usd_price;
+
+        let milestones = loaded.milestones;
+
+        loaded.__account__.milestones = milestones;
+
+        let milestone_count = loaded.milestone_count;
+
+        loaded.__account__.milestone_count = milestone_count;
+
+        let released_milestones = loaded.released_milestones;
+
+        loaded.__account__.released_milestones = released_milestones;
+
+        let ruling = loaded.ruling.clone();
+
+        loaded.__account__.ruling = ruling;
+
+        let protocol_fee_bps = loaded.protocol_fee_bps;
+
+        loaded.__account__.protocol_fee_bps = protocol_fee_bps;
+
+        let bond = loaded.bond;
+
+        loaded.__account__.bond = bond;
+
+        let item_mint = loaded.item_mint.clone();
+
+        loaded.__account__.item_mint = item_mint;
+
+        let item_vault = loaded.item_vault.clone();
+
+        loaded.__account__.item_vault = item_vault;
+
+        let item_amount = loaded.item_amount;
+
+        loaded.__account__.item_amount = item_amount;
+
+        let funding_deadline = loaded.funding_deadline;
+
+        loaded.__account__.funding_deadline = funding_deadline;
     }
 }
 
@@ -109,7 +402,7 @@ pub struct LoadedEscrowOrder<'info, 'entrypoint> {
     pub __account__: &'entrypoint mut Box<Account<'info, EscrowOrder>>,
     pub __programs__: &'entrypoint ProgramsMap<'info>,
     pub seller: Pubkey,
-    pub order_id: u16,
+    pub order_id: u64,
     pub bump: u8,
     pub seller_token_account: Pubkey,
     pub referee: Pubkey,
@@ -119,6 +412,102 @@ pub struct LoadedEscrowOrder<'info, 'entrypoint> {
     pub amount: u64,
     pub vault: Pubkey,
     pub state: OrderState,
+    pub disputer: Pubkey,
+    pub commission: u16,
+    pub release_window: i64,
+    pub release_deadline: i64,
+    pub dispute_window: i64,
+    pub dispute_deadline: i64,
+    pub dispute_fallback: DisputeFallback,
+    pub designated_buyer: Option<Pubkey>,
+    pub deposited_amount: u64,
+    pub mode: PaymentMode,
+    pub usd_price: Option<UsdPrice>,
+    pub milestones: [u64; 8],
+    pub milestone_count: u8,
+    pub released_milestones: u8,
+    pub ruling: Option<u16>,
+    pub protocol_fee_bps: u16,
+    pub bond: u64,
+    pub item_mint: Pubkey,
+    pub item_vault: Pubkey,
+    pub item_amount: u64,
+    pub funding_deadline: i64,
+}
+
+#[account]
+#[derive(Debug)]
+pub struct SellerProfile {
+    pub seller: Pubkey,
+    pub next_order_id: u64,
+    pub open_orders: u64,
+    pub settled_orders: u64,
+    pub disputed_orders: u64,
+    pub bump: u8,
+}
+
+impl<'info, 'entrypoint> SellerProfile {
+    pub fn load(
+        account: &'entrypoint mut Box<Account<'info, Self>>,
+        programs_map: &'entrypoint ProgramsMap<'info>,
+    ) -> Mutable<LoadedSellerProfile<'info, 'entrypoint>> {
+        let seller = account.seller.clone();
+        let next_order_id = account.next_order_id;
+        let open_orders = account.open_orders;
+        let settled_orders = account.settled_orders;
+        let disputed_orders = account.disputed_orders;
+        let bump = account.bump;
+
+        Mutable::new(LoadedSellerProfile {
+            __account__: account,
+            __programs__: programs_map,
+            seller,
+            next_order_id,
+            open_orders,
+            settled_orders,
+            disputed_orders,
+            bump,
+        })
+    }
+
+    pub fn store(loaded: Mutable<LoadedSellerProfile>) {
+        let mut loaded = loaded.borrow_mut();
+        let seller = loaded.seller.clone();
+
+        loaded.__account__.seller = seller;
+
+        let next_order_id = loaded.next_order_id;
+
+        loaded.__account__.next_order_id = next_order_id;
+
+        let open_orders = loaded.open_orders;
+
+        loaded.__account__.open_orders = open_orders;
+
+        let settled_orders = loaded.settled_orders;
+
+        loaded.__account__.settled_orders = settled_orders;
+
+        let disputed_orders = loaded.disputed_orders;
+
+        loaded.__account__.disputed_orders = disputed_orders;
+
+        let bump = loaded.bump;
+
+        loaded.__account__.bump = bump;
+    }
+}
+
+#[derive(Debug)]
+pub struct LoadedSellerProfile<'info, 'entrypoint> {
+    pub __account__: &'entrypoint mut Box<Account<'info, SellerProfile>>,
+    pub __programs__: &'entrypoint ProgramsMap<'info>,
+    pub seller: Pubkey,
+    pub next_order_id: u64,
+    pub open_orders: u64,
+    pub settled_orders: u64,
+    pub disputed_orders: u64,
+    pub bump: u8,
 }
 
 #[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Copy)]
@@ -129,6 +518,110 @@ pub enum OrderState {
     Settled,
     Refunded,
     Resolved,
+    Cancelled,
+}
+
+#[event]
+pub struct OrderStateChanged {
+    pub order: Pubkey,
+    pub actor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub old_state: Option<OrderState>,
+    pub new_state: OrderState,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("wrong vault inputted")]
+    WrongVault,
+    #[msg("order is not in the right state for this instruction")]
+    InvalidState,
+    #[msg("not your escrow order")]
+    NotBuyer,
+    #[msg("you are not the seller of this order")]
+    NotSeller,
+    #[msg("you are not referee")]
+    NotReferee,
+    #[msg("must release to the order's buyer or seller token account")]
+    WrongPayoutAccount,
+    // no longer returned since the referee token account is checked by
+    // constraints, kept so later error codes keep their numbers
+    #[msg("referee token account must hold the order mint and belong to the referee")]
+    WrongRefereeTokenAccount,
+    #[msg("commission cannot exceed 100%")]
+    CommissionTooHigh,
+    #[msg("arithmetic overflow")]
+    MathOverflow,
+    #[msg("release window must be positive")]
+    InvalidReleaseWindow,
+    #[msg("release deadline has not passed yet")]
+    ReleaseDeadlineNotPassed,
+    #[msg("dispute window must be positive")]
+    InvalidDisputeWindow,
+    #[msg("dispute deadline has not passed yet")]
+    DisputeDeadlineNotPassed,
+    #[msg("this order is reserved for another buyer")]
+    NotDesignatedBuyer,
+    #[msg("vault holds no tokens beyond the deposit")]
+    NothingToSweep,
+    #[msg("mint does not match the order")]
+    WrongMint,
+    #[msg("order is not paid in this mode")]
+    WrongPaymentMode,
+    #[msg("payout would leave the order below rent exemption")]
+    NotRentExempt,
+    #[msg("USD pricing is not enabled in this build")]
+    UsdPricingDisabled,
+    #[msg("order is priced in USD")]
+    PricedInUsd,
+    #[msg("price feed does not match the order")]
+    WrongPriceFeed,
+    #[msg("price feed could not be read")]
+    InvalidPrice,
+    #[msg("price is older than the allowed age")]
+    StalePrice,
+    #[msg("price confidence interval is too wide")]
+    PriceTooUncertain,
+    #[msg("milestones must be positive and add up to the order amount")]
+    InvalidMilestones,
+    #[msg("milestone does not exist")]
+    InvalidMilestone,
+    #[msg("milestone has already been released")]
+    MilestoneReleased,
+    #[msg("buyer share must be at most 10000 bps")]
+    InvalidRuling,
+    #[msg("refund is larger than the deposit")]
+    RefundTooLarge,
+    #[msg("new orders and deposits are paused")]
+    Paused,
+    #[msg("signer is not the admin")]
+    NotAdmin,
+    #[msg("signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("protocol fee cannot exceed 10000 bps")]
+    FeeTooHigh,
+    #[msg("swap orders must escrow a non-zero item amount")]
+    InvalidItemAmount,
+    #[msg("crowdfund target must be greater than zero")]
+    InvalidTarget,
+    #[msg("funding deadline must be in the future")]
+    InvalidFundingDeadline,
+    #[msg("contribution must be greater than zero")]
+    InvalidContribution,
+    #[msg("funding deadline has passed")]
+    FundingClosed,
+    #[msg("funding deadline has not passed yet")]
+    FundingOpen,
+    #[msg("price feed is not owned by the Pyth oracle")]
+    PriceFeedNotPyth,
+    #[msg("converted amount is above the buyer's maximum")]
+    SlippageExceeded,
+    #[msg("refund must be greater than zero")]
+    InvalidRefund,
+    #[msg("bond vault must be passed exactly when the order has a bond")]
+    WrongBondVault,
 }
 
 impl Default for OrderState {
@@ -137,127 +630,294 @@ impl Default for OrderState {
     }
 }
 
-pub fn deposit_handler<'info>(
-    mut buyer: SeahorseSigner<'info, '_>,
-    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
-    mut buyer_token_account: SeahorseAccount<'info, '_, TokenAccount>,
-    mut vault: SeahorseAccount<'info, '_, TokenAccount>,
-) -> () {
-    if !(order.borrow().state == OrderState::Pending) {
-        panic!("cannot deposit again");
+#[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Copy)]
+pub enum DisputeFallback {
+    Refund,
+    Split,
+}
+
+impl Default for DisputeFallback {
+    fn default() -> Self {
+        DisputeFallback::Refund
     }
+}
+
+#[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Copy)]
+pub enum PaymentMode {
+    Token,
+    Native,
+    Swap,
+    Crowdfund,
+}
 
-    if !(vault.key() == order.borrow().vault) {
-        panic!("wrong vault inputted");
+impl Default for PaymentMode {
+    fn default() -> Self {
+        PaymentMode::Token
     }
+}
 
-    token::transfer(
-        CpiContext::new(
-            buyer_token_account.programs.get("token_program"),
-            token::Transfer {
-                from: buyer_token_account.to_account_info(),
-                authority: buyer.clone().to_account_info(),
-                to: vault.clone().to_account_info(),
-            },
-        ),
-        order.borrow().amount.clone(),
-    )
-    .unwrap();
+#[derive(Clone, Debug, PartialEq, AnchorSerialize, AnchorDeserialize, Copy)]
+pub struct UsdPrice {
+    pub cents: u64,
+    pub price_feed: Pubkey,
+}
 
-    assign!(order.borrow_mut().buyer, buyer.key());
+// Token-2022 mints can withhold a transfer fee from the recipient, so a
+// transfer of `amount` only lands `amount - fee` in the destination
+fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
 
-    assign!(
-        order.borrow_mut().buyer_token_account,
-        buyer_token_account.key()
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(error!(EscrowError::MathOverflow)),
+        Err(_) => Ok(0),
+    }
+}
+
+// Token-2022 will not close an account that still holds withheld transfer
+// fees, so they are harvested to the mint first
+fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &SeahorseInterfaceAccount<'info, '_, Mint>,
+    account: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    let withheld = {
+        let info = account.to_account_info();
+        let data = info.try_borrow_data()?;
+
+        match StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data) {
+            Ok(state) => state
+                .get_extension::<TransferFeeAmount>()
+                .map(|fee| u64::from(fee.withheld_amount))
+                .unwrap_or(0),
+            Err(_) => 0,
+        }
+    };
+
+    if withheld == 0 {
+        return Ok(());
+    }
+
+    solana_program::program::invoke(
+        &harvest_withheld_tokens_to_mint(token_program.key, &mint.key(), &[&account.key()])?,
+        &[
+            mint.to_account_info(),
+            account.to_account_info(),
+            token_program.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+// Native orders hold the deposit as lamports on the order account itself,
+// which the program owns and can debit directly while it stays rent-exempt
+fn pay_lamports(order: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let remaining = order
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    require!(
+        remaining >= Rent::get()?.minimum_balance(order.data_len()),
+        EscrowError::NotRentExempt
     );
 
-    assign!(order.borrow_mut().state, OrderState::Deposited);
+    **order.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    Ok(())
 }
 
-pub fn dispute_handler<'info>(
-    mut buyer: SeahorseSigner<'info, '_>,
-    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
-) -> () {
-    if !(buyer.key() == order.borrow().buyer) {
-        panic!("not your escrow order");
-    }
+// Matches the length of EscrowOrder::milestones
+pub const MAX_MILESTONES: usize = 8;
 
-    if !(order.borrow().state == OrderState::Deposited) {
-        panic!("not deposited or settled");
-    }
+// Prices older than this many seconds are rejected
+pub const MAX_PRICE_AGE: u64 = 60;
 
-    assign!(order.borrow_mut().state, OrderState::Dispute);
+// The confidence interval may be at most this share of the price, in bps
+pub const MAX_PRICE_CONF_BPS: u64 = 200;
+
+// The Pyth oracle programs on mainnet and devnet, anything else could
+// publish any price it likes
+pub mod pyth_mainnet {
+    use super::*;
+
+    declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
 }
 
-pub fn init_order_handler<'info>(
-    mut seller: SeahorseSigner<'info, '_>,
-    mut seller_token_account: SeahorseAccount<'info, '_, TokenAccount>,
-    mut mint: SeahorseAccount<'info, '_, Mint>,
-    mut order: Empty<Mutable<LoadedEscrowOrder<'info, '_>>>,
-    mut vault: Empty<SeahorseAccount<'info, '_, TokenAccount>>,
-    mut order_id: u16,
-    mut referee: Pubkey,
-    mut amount: u64,
-) -> () {
-    let mut bump = order.bump.unwrap();
-    let mut order = order.account.clone();
-    let mut vault = vault.account.clone();
+pub mod pyth_devnet {
+    use super::*;
 
-    assign!(order.borrow_mut().seller, seller.key());
+    declare_id!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
+}
 
-    assign!(
-        order.borrow_mut().seller_token_account,
-        seller_token_account.key()
+// Converts a USD price into mint units, rounding up so the seller is never
+// paid less than the listed price
+#[cfg(feature = "usd-pricing")]
+fn usd_to_token_amount(price_feed: &AccountInfo, cents: u64, decimals: u8) -> Result<u64> {
+    require!(
+        pyth_mainnet::check_id(price_feed.owner) || pyth_devnet::check_id(price_feed.owner),
+        EscrowError::PriceFeedNotPyth
     );
 
-    assign!(order.borrow_mut().mint, mint.key());
+    let feed = pyth_sdk_solana::load_price_feed_from_account_info(price_feed)
+        .map_err(|_| error!(EscrowError::InvalidPrice))?;
 
-    assign!(order.borrow_mut().vault, vault.key());
+    let price = feed
+        .get_price_no_older_than(Clock::get()?.unix_timestamp, MAX_PRICE_AGE)
+        .ok_or(EscrowError::StalePrice)?;
 
-    assign!(order.borrow_mut().order_id, order_id);
+    require!(
+        price.price > 0 && price.expo <= 0,
+        EscrowError::InvalidPrice
+    );
 
-    assign!(order.borrow_mut().referee, referee);
+    let value = price.price as u128;
 
-    assign!(order.borrow_mut().amount, amount);
+    require!(
+        (price.conf as u128) * 10000 <= value * MAX_PRICE_CONF_BPS as u128,
+        EscrowError::PriceTooUncertain
+    );
 
-    assign!(order.borrow_mut().bump, bump);
+    // cents * 10^(decimals - expo) / (100 * price)
+    let scale = 10u128
+        .checked_pow(decimals as u32 + price.expo.unsigned_abs())
+        .ok_or(EscrowError::MathOverflow)?;
 
-    assign!(order.borrow_mut().state, OrderState::Pending);
-}
+    let numerator = (cents as u128)
+        .checked_mul(scale)
+        .ok_or(EscrowError::MathOverflow)?;
 
-pub fn refund_handler<'info>(
-    mut referee: SeahorseSigner<'info, '_>,
-    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
-    mut vault: SeahorseAccount<'info, '_, TokenAccount>,
-    mut buyer_token_account: SeahorseAccount<'info, '_, TokenAccount>,
-) -> () {
-    if !(vault.key() == order.borrow().vault) {
-        panic!("wrong vault inputted");
-    }
+    let denominator = value * 100;
 
-    if !(order.borrow().state == OrderState::Dispute) {
-        panic!("cannot refund before dispute");
-    }
+    u64::try_from((numerator + denominator - 1) / denominator)
+        .map_err(|_| error!(EscrowError::MathOverflow))
+}
 
-    if !(buyer_token_account.key() == order.borrow().buyer_token_account) {
-        panic!("must relase to buyer token account");
-    }
+#[cfg(not(feature = "usd-pricing"))]
+fn usd_to_token_amount(price_feed: &AccountInfo, cents: u64, decimals: u8) -> Result<u64> {
+    err!(EscrowError::UsdPricingDisabled)
+}
 
-    if !(referee.key() == order.borrow().referee) {
-        panic!("you are not referee");
-    }
+// Moves the order amount from the buyer into the vault and starts the
+// release timer
+fn lock_deposit<'info>(
+    buyer: &SeahorseSigner<'info, '_>,
+    order: &Mutable<LoadedEscrowOrder<'info, '_>>,
+    mint: &SeahorseInterfaceAccount<'info, '_, Mint>,
+    buyer_token_account: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    vault: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Pending,
+        EscrowError::InvalidState
+    );
 
-    let mut seller = order.borrow().seller;
-    let mut order_id = order.borrow().order_id;
-    let mut bump = order.borrow().bump;
+    if let Some(designated_buyer) = order.borrow().designated_buyer {
+        require!(
+            buyer.key() == designated_buyer,
+            EscrowError::NotDesignatedBuyer
+        );
+    }
 
-    token::transfer(
+    token_interface::transfer_checked(
+        CpiContext::new(
+            buyer_token_account.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: buyer_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: vault.clone().to_account_info(),
+                authority: buyer.clone().to_account_info(),
+            },
+        ),
+        order.borrow().amount.clone(),
+        mint.decimals,
+    )?;
+
+    assign!(order.borrow_mut().buyer, buyer.key());
+
+    let mut fee = transfer_fee(&mint.to_account_info(), order.borrow().amount)?;
+
+    assign!(
+        order.borrow_mut().deposited_amount,
+        order
+            .borrow()
+            .amount
+            .checked_sub(fee)
+            .ok_or(EscrowError::MathOverflow)?
+    );
+
+    assign!(
+        order.borrow_mut().buyer_token_account,
+        buyer_token_account.key()
+    );
+
+    // the seller can be paid without the buyer once this passes, unless
+    // a dispute is opened first
+    let mut release_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(order.borrow().release_window)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    assign!(order.borrow_mut().release_deadline, release_deadline);
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Deposited);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: order.borrow().amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Deposited,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// The protocol fee snapshotted on the order, rounded down so any remainder
+// goes to the seller
+fn protocol_fee(order: &Mutable<LoadedEscrowOrder>, amount: u64) -> Result<u64> {
+    Ok(((amount as u128)
+        .checked_mul(order.borrow().protocol_fee_bps as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64)
+}
+
+// Pays the seller out of the vault, less the protocol fee snapshotted on
+// the order, which goes to the treasury
+fn pay_seller<'info>(
+    order: &Mutable<LoadedEscrowOrder<'info, '_>>,
+    mint: &SeahorseInterfaceAccount<'info, '_, Mint>,
+    vault: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    seller_token_account: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    treasury_token_account: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    let mut fee = protocol_fee(order, amount)?;
+    let mut payout = amount.checked_sub(fee).ok_or(EscrowError::MathOverflow)?;
+
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             vault.programs.get("token_program"),
-            token::Transfer {
+            token_interface::TransferChecked {
                 from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: treasury_token_account.clone().to_account_info(),
                 authority: order.borrow().__account__.to_account_info(),
-                to: buyer_token_account.clone().to_account_info(),
             },
             &[Mutable::new(vec![
                 "order".to_string().as_bytes().as_ref(),
@@ -268,46 +928,334 @@ pub fn refund_handler<'info>(
             .borrow()
             .as_slice()],
         ),
-        vault.amount,
-    )
-    .unwrap();
+        fee,
+        mint.decimals,
+    )?;
 
-    assign!(order.borrow_mut().state, OrderState::Refunded);
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: seller_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        payout,
+        mint.decimals,
+    )?;
+
+    Ok(())
 }
 
-pub fn release_handler<'info>(
-    mut buyer: SeahorseSigner<'info, '_>,
-    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
-    mut vault: SeahorseAccount<'info, '_, TokenAccount>,
-    mut seller_token_account: SeahorseAccount<'info, '_, TokenAccount>,
-) -> () {
-    if !(vault.key() == order.borrow().vault) {
-        panic!("wrong vault inputted");
-    }
+// The native counterpart of pay_seller, paying out of the order's lamports
+fn pay_seller_native<'info>(
+    order: &Mutable<LoadedEscrowOrder<'info, '_>>,
+    seller: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let mut fee = protocol_fee(order, amount)?;
+    let mut payout = amount.checked_sub(fee).ok_or(EscrowError::MathOverflow)?;
+
+    pay_lamports(&order.borrow().__account__.to_account_info(), treasury, fee)?;
+
+    pay_lamports(
+        &order.borrow().__account__.to_account_info(),
+        seller,
+        payout,
+    )?;
 
-    if !(order.borrow().state == OrderState::Deposited) {
-        panic!("cannot release before deposit");
+    Ok(())
+}
+
+// Pays out of the seller bond, which sits in its own vault so it never
+// mixes with the buyer's deposit
+fn pay_bond<'info>(
+    order: &Mutable<LoadedEscrowOrder<'info, '_>>,
+    mint: &SeahorseInterfaceAccount<'info, '_, Mint>,
+    bond_vault: &Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    to: &SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
     }
 
-    if !(seller_token_account.key() == order.borrow().seller_token_account) {
-        panic!("must relase to seller token account");
+    let mut bond_vault = bond_vault.as_ref().ok_or(EscrowError::WrongBondVault)?;
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            bond_vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: bond_vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    Ok(())
+}
+
+// Registers a new order with the seller profile, which is set up on the
+// seller's first order, and snapshots the protocol fee so later changes only
+// apply to new orders
+fn open_order<'info>(
+    seller: &SeahorseSigner<'info, '_>,
+    config: &SeahorseAccount<'info, '_, Config>,
+    order: &Mutable<LoadedEscrowOrder<'info, '_>>,
+    seller_profile: &Mutable<LoadedSellerProfile<'info, '_>>,
+    profile_bump: u8,
+) -> Result<u64> {
+    // order ids come from the seller profile, so they never collide
+    let mut order_id = seller_profile.borrow().next_order_id;
+
+    assign!(seller_profile.borrow_mut().seller, seller.key());
+
+    assign!(seller_profile.borrow_mut().bump, profile_bump);
+
+    assign!(
+        seller_profile.borrow_mut().next_order_id,
+        order_id.checked_add(1).ok_or(EscrowError::MathOverflow)?
+    );
+
+    assign!(
+        seller_profile.borrow_mut().open_orders,
+        seller_profile
+            .borrow()
+            .open_orders
+            .checked_add(1)
+            .ok_or(EscrowError::MathOverflow)?
+    );
+
+    assign!(order.borrow_mut().seller, seller.key());
+
+    assign!(order.borrow_mut().order_id, order_id);
+
+    assign!(order.borrow_mut().protocol_fee_bps, config.fee_bps);
+
+    Ok(order_id)
+}
+
+// Keeps the seller profile counters in step with the order state
+fn track_state(seller_profile: &Mutable<LoadedSellerProfile>, new_state: OrderState) -> Result<()> {
+    match new_state {
+        OrderState::Dispute => {
+            let mut disputed_orders = seller_profile
+                .borrow()
+                .disputed_orders
+                .checked_add(1)
+                .ok_or(EscrowError::MathOverflow)?;
+
+            assign!(seller_profile.borrow_mut().disputed_orders, disputed_orders);
+        }
+        OrderState::Settled
+        | OrderState::Refunded
+        | OrderState::Resolved
+        | OrderState::Cancelled => {
+            let mut open_orders = seller_profile
+                .borrow()
+                .open_orders
+                .checked_sub(1)
+                .ok_or(EscrowError::MathOverflow)?;
+
+            assign!(seller_profile.borrow_mut().open_orders, open_orders);
+
+            if new_state == OrderState::Settled {
+                let mut settled_orders = seller_profile
+                    .borrow()
+                    .settled_orders
+                    .checked_add(1)
+                    .ok_or(EscrowError::MathOverflow)?;
+
+                assign!(seller_profile.borrow_mut().settled_orders, settled_orders);
+            }
+        }
+        _ => {}
     }
 
-    if !(buyer.key() == order.borrow().buyer) {
-        panic!("not your escrow order");
+    Ok(())
+}
+
+pub fn accept_admin_handler<'info>(
+    mut new_admin: SeahorseSigner<'info, '_>,
+    mut config: Mutable<LoadedConfig<'info, '_>>,
+) -> Result<()> {
+    assign!(config.borrow_mut().admin, new_admin.key());
+
+    assign!(config.borrow_mut().pending_admin, None);
+
+    Ok(())
+}
+
+pub fn cancel_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    // swap and crowdfund orders hold more than the payment vault and have
+    // their own close paths
+    require!(
+        order.borrow().mode == PaymentMode::Token,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        order.borrow().state == OrderState::Pending,
+        EscrowError::InvalidState
+    );
+
+    // the whole bond vault goes back, including anything sent to it
+    // directly, so it can be closed
+    let mut bond = bond_vault
+        .as_ref()
+        .map_or(order.borrow().bond, |bond_vault| bond_vault.amount);
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    harvest_withheld_fees(&vault.programs.get("token_program"), &mint, &vault)?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        vault.programs.get("token_program"),
+        token_interface::CloseAccount {
+            account: vault.to_account_info(),
+            destination: seller.to_account_info(),
+            authority: order.borrow().__account__.to_account_info(),
+        },
+        &[Mutable::new(vec![
+            "order".to_string().as_bytes().as_ref(),
+            seller.key().as_ref(),
+            order_id.to_le_bytes().as_ref(),
+            bump.to_le_bytes().as_ref(),
+        ])
+        .borrow()
+        .as_slice()],
+    ))?;
+
+    // orders without a bond never had a bond vault
+    if let Some(bond_vault) = &bond_vault {
+        harvest_withheld_fees(
+            &bond_vault.programs.get("token_program"),
+            &mint,
+            &bond_vault,
+        )?;
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            bond_vault.programs.get("token_program"),
+            token_interface::CloseAccount {
+                account: bond_vault.to_account_info(),
+                destination: seller.to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.key().as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ))?;
     }
 
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Cancelled);
+
+    track_state(&seller_profile, OrderState::Cancelled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: order.borrow().amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Cancelled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn claim_after_dispute_timeout_handler<'info>(
+    mut caller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp >= order.borrow().dispute_deadline,
+        EscrowError::DisputeDeadlineNotPassed
+    );
+
     let mut seller = order.borrow().seller;
     let mut order_id = order.borrow().order_id;
     let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // a split rounds the seller's half down, so an odd unit goes to the buyer
+    let (mut buyer_share, mut new_state) = match order.borrow().dispute_fallback {
+        DisputeFallback::Refund => (deposited_amount, OrderState::Refunded),
+        DisputeFallback::Split => (
+            deposited_amount - deposited_amount / 2,
+            OrderState::Resolved,
+        ),
+    };
+
+    let mut seller_share = deposited_amount
+        .checked_sub(buyer_share)
+        .ok_or(EscrowError::MathOverflow)?;
 
-    token::transfer(
+    token_interface::transfer_checked(
         CpiContext::new_with_signer(
             vault.programs.get("token_program"),
-            token::Transfer {
+            token_interface::TransferChecked {
                 from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: buyer_token_account.clone().to_account_info(),
                 authority: order.borrow().__account__.to_account_info(),
-                to: seller_token_account.clone().to_account_info(),
             },
             &[Mutable::new(vec![
                 "order".to_string().as_bytes().as_ref(),
@@ -318,9 +1266,2306 @@ pub fn release_handler<'info>(
             .borrow()
             .as_slice()],
         ),
-        vault.amount,
-    )
-    .unwrap();
+        buyer_share,
+        mint.decimals,
+    )?;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        seller_share,
+    )?;
+
+    // the bond follows the fallback, a refund forfeits it to the buyer
+    let mut bond = order.borrow().bond;
+
+    let mut buyer_bond = match order.borrow().dispute_fallback {
+        DisputeFallback::Refund => bond,
+        DisputeFallback::Split => bond - bond / 2,
+    };
+
+    pay_bond(&order, &mint, &bond_vault, &buyer_token_account, buyer_bond)?;
+
+    pay_bond(
+        &order,
+        &mint,
+        &bond_vault,
+        &seller_token_account,
+        bond - buyer_bond,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, new_state);
+
+    track_state(&seller_profile, new_state)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: caller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: new_state,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn claim_after_dispute_timeout_native_handler<'info>(
+    mut caller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut buyer: WithPrograms<'info, '_, SystemAccount<'info>>,
+    mut seller: WithPrograms<'info, '_, SystemAccount<'info>>,
+    mut treasury: WithPrograms<'info, '_, SystemAccount<'info>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp >= order.borrow().dispute_deadline,
+        EscrowError::DisputeDeadlineNotPassed
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // a split rounds the seller's half down, so an odd lamport goes to the buyer
+    let (mut buyer_share, mut new_state) = match order.borrow().dispute_fallback {
+        DisputeFallback::Refund => (deposited_amount, OrderState::Refunded),
+        DisputeFallback::Split => (
+            deposited_amount - deposited_amount / 2,
+            OrderState::Resolved,
+        ),
+    };
+
+    let mut seller_share = deposited_amount
+        .checked_sub(buyer_share)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    pay_lamports(
+        &order.borrow().__account__.to_account_info(),
+        &buyer.to_account_info(),
+        buyer_share,
+    )?;
+
+    pay_seller_native(
+        &order,
+        &seller.to_account_info(),
+        &treasury.to_account_info(),
+        seller_share,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, new_state);
+
+    track_state(&seller_profile, new_state)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: caller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: new_state,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn claim_after_timeout_handler<'info>(
+    mut caller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    // crowdfund orders have no release deadline and only settle with
+    // release_crowdfund
+    require!(
+        order.borrow().mode == PaymentMode::Token,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp >= order.borrow().release_deadline,
+        EscrowError::ReleaseDeadlineNotPassed
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        deposited_amount,
+    )?;
+
+    let mut bond = order.borrow().bond;
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: caller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn claim_after_timeout_native_handler<'info>(
+    mut caller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut seller: WithPrograms<'info, '_, SystemAccount<'info>>,
+    mut treasury: WithPrograms<'info, '_, SystemAccount<'info>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp >= order.borrow().release_deadline,
+        EscrowError::ReleaseDeadlineNotPassed
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    pay_seller_native(
+        &order,
+        &seller.to_account_info(),
+        &treasury.to_account_info(),
+        deposited_amount,
+    )?;
+
+    let mut old_state = order.borrow().state;
 
     assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: caller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn close_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    // swap and crowdfund orders are closed with close_swap_order and
+    // close_crowdfund_order
+    require!(
+        order.borrow().mode == PaymentMode::Token,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        order.borrow().state == OrderState::Settled
+            || order.borrow().state == OrderState::Refunded
+            || order.borrow().state == OrderState::Resolved,
+        EscrowError::InvalidState
+    );
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    harvest_withheld_fees(&vault.programs.get("token_program"), &mint, &vault)?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        vault.programs.get("token_program"),
+        token_interface::CloseAccount {
+            account: vault.to_account_info(),
+            destination: seller.to_account_info(),
+            authority: order.borrow().__account__.to_account_info(),
+        },
+        &[Mutable::new(vec![
+            "order".to_string().as_bytes().as_ref(),
+            seller.key().as_ref(),
+            order_id.to_le_bytes().as_ref(),
+            bump.to_le_bytes().as_ref(),
+        ])
+        .borrow()
+        .as_slice()],
+    ))?;
+
+    require!(
+        bond_vault.is_some() || order.borrow().bond == 0,
+        EscrowError::WrongBondVault
+    );
+
+    // the bond was paid out when the order finished, anything still in the
+    // vault was sent to it directly and goes to the seller so it can be closed
+    let mut leftover = bond_vault
+        .as_ref()
+        .map_or(0, |bond_vault| bond_vault.amount);
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, leftover)?;
+
+    // orders without a bond never had a bond vault
+    if let Some(bond_vault) = &bond_vault {
+        harvest_withheld_fees(
+            &bond_vault.programs.get("token_program"),
+            &mint,
+            &bond_vault,
+        )?;
+
+        token_interface::close_account(CpiContext::new_with_signer(
+            bond_vault.programs.get("token_program"),
+            token_interface::CloseAccount {
+                account: bond_vault.to_account_info(),
+                destination: seller.to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.key().as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ))?;
+    }
+
+    Ok(())
+}
+pub fn close_contribution_handler<'info>(
+    mut contributor: SeahorseSigner<'info, '_>,
+    mut order: UncheckedAccount<'info>,
+    mut contribution: Mutable<LoadedContribution<'info, '_>>,
+) -> Result<()> {
+    // a funded crowdfund keeps the contributions, so once it settles the
+    // receipt is only rent. A closed order was either settled or cancelled
+    // after every contribution was reclaimed
+    if !order.data_is_empty() {
+        require!(order.owner == &id(), ErrorCode::AccountOwnedByWrongProgram);
+
+        let mut order = EscrowOrder::try_deserialize(&mut &order.try_borrow_data()?[..])?;
+
+        require!(
+            order.mode == PaymentMode::Crowdfund && order.state == OrderState::Settled,
+            EscrowError::InvalidState
+        );
+    }
+
+    Ok(())
+}
+
+pub fn close_crowdfund_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Crowdfund,
+        EscrowError::WrongPaymentMode
+    );
+
+    // an unfunded order can only go once every contributor has reclaimed
+    require!(
+        order.borrow().state == OrderState::Settled
+            || (order.borrow().state == OrderState::Pending
+                && order.borrow().deposited_amount == 0),
+        EscrowError::InvalidState
+    );
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    if order.borrow().state == OrderState::Pending {
+        assign!(order.borrow_mut().state, OrderState::Cancelled);
+
+        track_state(&seller_profile, OrderState::Cancelled)?;
+
+        emit!(OrderStateChanged {
+            order: order.borrow().__account__.key(),
+            actor: seller.key(),
+            mint: order.borrow().mint,
+            amount: order.borrow().amount,
+            old_state: Some(OrderState::Pending),
+            new_state: OrderState::Cancelled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    harvest_withheld_fees(&vault.programs.get("token_program"), &mint, &vault)?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        vault.programs.get("token_program"),
+        token_interface::CloseAccount {
+            account: vault.to_account_info(),
+            destination: seller.to_account_info(),
+            authority: order.borrow().__account__.to_account_info(),
+        },
+        &[Mutable::new(vec![
+            "order".to_string().as_bytes().as_ref(),
+            seller.key().as_ref(),
+            order_id.to_le_bytes().as_ref(),
+            bump.to_le_bytes().as_ref(),
+        ])
+        .borrow()
+        .as_slice()],
+    ))?;
+
+    Ok(())
+}
+
+pub fn close_native_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    // the whole balance goes back to the seller, so nothing may be owed
+    // to the buyer
+    require!(
+        order.borrow().state == OrderState::Pending
+            || order.borrow().state == OrderState::Settled
+            || order.borrow().state == OrderState::Refunded
+            || order.borrow().state == OrderState::Resolved,
+        EscrowError::InvalidState
+    );
+
+    if order.borrow().state == OrderState::Pending {
+        assign!(order.borrow_mut().state, OrderState::Cancelled);
+
+        track_state(&seller_profile, OrderState::Cancelled)?;
+
+        emit!(OrderStateChanged {
+            order: order.borrow().__account__.key(),
+            actor: seller.key(),
+            mint: order.borrow().mint,
+            amount: order.borrow().amount,
+            old_state: Some(OrderState::Pending),
+            new_state: OrderState::Cancelled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+pub fn close_swap_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut item_mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut item_vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_item_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Swap,
+        EscrowError::WrongPaymentMode
+    );
+
+    // a swap order is either still waiting for a buyer or already settled
+    require!(
+        order.borrow().state == OrderState::Pending || order.borrow().state == OrderState::Settled,
+        EscrowError::InvalidState
+    );
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    if order.borrow().state == OrderState::Pending {
+        assign!(order.borrow_mut().state, OrderState::Cancelled);
+
+        track_state(&seller_profile, OrderState::Cancelled)?;
+
+        emit!(OrderStateChanged {
+            order: order.borrow().__account__.key(),
+            actor: seller.key(),
+            mint: order.borrow().mint,
+            amount: order.borrow().amount,
+            old_state: Some(OrderState::Pending),
+            new_state: OrderState::Cancelled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    // everything in the item vault goes back to the seller, which also
+    // clears anything sent to it directly so the vault can be closed
+    let mut item_left = item_vault.amount;
+
+    if item_left > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                item_vault.programs.get("item_token_program"),
+                token_interface::TransferChecked {
+                    from: item_vault.to_account_info(),
+                    mint: item_mint.to_account_info(),
+                    to: seller_item_account.clone().to_account_info(),
+                    authority: order.borrow().__account__.to_account_info(),
+                },
+                &[Mutable::new(vec![
+                    "order".to_string().as_bytes().as_ref(),
+                    seller.key().as_ref(),
+                    order_id.to_le_bytes().as_ref(),
+                    bump.to_le_bytes().as_ref(),
+                ])
+                .borrow()
+                .as_slice()],
+            ),
+            item_left,
+            item_mint.decimals,
+        )?;
+    }
+
+    harvest_withheld_fees(&vault.programs.get("token_program"), &mint, &vault)?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        vault.programs.get("token_program"),
+        token_interface::CloseAccount {
+            account: vault.to_account_info(),
+            destination: seller.to_account_info(),
+            authority: order.borrow().__account__.to_account_info(),
+        },
+        &[Mutable::new(vec![
+            "order".to_string().as_bytes().as_ref(),
+            seller.key().as_ref(),
+            order_id.to_le_bytes().as_ref(),
+            bump.to_le_bytes().as_ref(),
+        ])
+        .borrow()
+        .as_slice()],
+    ))?;
+
+    harvest_withheld_fees(
+        &item_vault.programs.get("item_token_program"),
+        &item_mint,
+        &item_vault,
+    )?;
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        item_vault.programs.get("item_token_program"),
+        token_interface::CloseAccount {
+            account: item_vault.to_account_info(),
+            destination: seller.to_account_info(),
+            authority: order.borrow().__account__.to_account_info(),
+        },
+        &[Mutable::new(vec![
+            "order".to_string().as_bytes().as_ref(),
+            seller.key().as_ref(),
+            order_id.to_le_bytes().as_ref(),
+            bump.to_le_bytes().as_ref(),
+        ])
+        .borrow()
+        .as_slice()],
+    ))?;
+
+    Ok(())
+}
+
+pub fn contribute_handler<'info>(
+    mut contributor: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut contributor_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut contribution: Empty<Mutable<LoadedContribution<'info, '_>>>,
+    mut amount: u64,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Crowdfund,
+        EscrowError::WrongPaymentMode
+    );
+
+    require!(
+        order.borrow().state == OrderState::Pending,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp < order.borrow().funding_deadline,
+        EscrowError::FundingClosed
+    );
+
+    require!(amount > 0, EscrowError::InvalidContribution);
+
+    let mut contribution_bump = contribution.bump.unwrap();
+    let mut contribution = contribution.account.clone();
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            contributor_token_account.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: contributor_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: vault.clone().to_account_info(),
+                authority: contributor.clone().to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    // the receipt records what the vault received, which is what a
+    // contributor can reclaim
+    let mut fee = transfer_fee(&mint.to_account_info(), amount)?;
+    let mut received = amount.checked_sub(fee).ok_or(EscrowError::MathOverflow)?;
+
+    assign!(
+        contribution.borrow_mut().order,
+        order.borrow().__account__.key()
+    );
+
+    assign!(contribution.borrow_mut().contributor, contributor.key());
+
+    assign!(contribution.borrow_mut().bump, contribution_bump);
+
+    assign!(
+        contribution.borrow_mut().amount,
+        contribution
+            .borrow()
+            .amount
+            .checked_add(received)
+            .ok_or(EscrowError::MathOverflow)?
+    );
+
+    let mut deposited_amount = order
+        .borrow()
+        .deposited_amount
+        .checked_add(received)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    assign!(order.borrow_mut().deposited_amount, deposited_amount);
+
+    // reaching the target closes funding, the last contribution may
+    // overshoot it
+    if deposited_amount >= order.borrow().amount {
+        let mut old_state = order.borrow().state;
+
+        assign!(order.borrow_mut().state, OrderState::Deposited);
+
+        emit!(OrderStateChanged {
+            order: order.borrow().__account__.key(),
+            actor: contributor.key(),
+            mint: order.borrow().mint,
+            amount: deposited_amount,
+            old_state: Some(old_state),
+            new_state: OrderState::Deposited,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
+pub fn deposit_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(order.borrow().usd_price.is_none(), EscrowError::PricedInUsd);
+
+    // swap orders have to settle in the same instruction with deposit_swap
+    require!(
+        order.borrow().mode == PaymentMode::Token,
+        EscrowError::WrongPaymentMode
+    );
+
+    lock_deposit(&buyer, &order, &mint, &buyer_token_account, &vault)
+}
+
+pub fn deposit_native_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Pending,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    if let Some(designated_buyer) = order.borrow().designated_buyer {
+        require!(
+            buyer.key() == designated_buyer,
+            EscrowError::NotDesignatedBuyer
+        );
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            buyer.programs.get("system_program"),
+            system_program::Transfer {
+                from: buyer.to_account_info(),
+                to: order.borrow().__account__.to_account_info(),
+            },
+        ),
+        order.borrow().amount,
+    )?;
+
+    assign!(order.borrow_mut().buyer, buyer.key());
+
+    assign!(order.borrow_mut().deposited_amount, order.borrow().amount);
+
+    // the seller can be paid without the buyer once this passes, unless
+    // a dispute is opened first
+    let mut release_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(order.borrow().release_window)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    assign!(order.borrow_mut().release_deadline, release_deadline);
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Deposited);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: order.borrow().amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Deposited,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn deposit_swap_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut item_mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut buyer_item_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut item_vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Swap,
+        EscrowError::WrongPaymentMode
+    );
+
+    lock_deposit(&buyer, &order, &mint, &buyer_token_account, &vault)?;
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // the payment and the item change hands in the same instruction, so
+    // neither side has to trust the other and no referee is needed
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        deposited_amount,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            item_vault.programs.get("item_token_program"),
+            token_interface::TransferChecked {
+                from: item_vault.to_account_info(),
+                mint: item_mint.to_account_info(),
+                to: buyer_item_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        order.borrow().item_amount,
+        item_mint.decimals,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+pub fn deposit_usd_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut price_feed: UncheckedAccount<'info>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut max_amount: u64,
+) -> Result<()> {
+    let mut usd_price = order
+        .borrow()
+        .usd_price
+        .ok_or(EscrowError::WrongPriceFeed)?;
+
+    let mut amount = usd_to_token_amount(&price_feed, usd_price.cents, mint.decimals)?;
+
+    // the price can move between signing and landing, so the buyer caps it
+    require!(amount <= max_amount, EscrowError::SlippageExceeded);
+
+    // the amount is fixed at the feed price when the buyer commits
+    assign!(order.borrow_mut().amount, amount);
+
+    lock_deposit(&buyer, &order, &mint, &buyer_token_account, &vault)
+}
+
+pub fn dispute_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    assign!(order.borrow_mut().disputer, buyer.key());
+
+    // anyone can apply the dispute fallback once this passes
+    let mut dispute_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(order.borrow().dispute_window)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    assign!(order.borrow_mut().dispute_deadline, dispute_deadline);
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Dispute);
+
+    track_state(&seller_profile, OrderState::Dispute)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: order.borrow().amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Dispute,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn init_crowdfund_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut config: SeahorseAccount<'info, '_, Config>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut order: Empty<Mutable<LoadedEscrowOrder<'info, '_>>>,
+    mut seller_profile: Empty<Mutable<LoadedSellerProfile<'info, '_>>>,
+    mut vault: Empty<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut target: u64,
+    mut funding_deadline: i64,
+) -> Result<()> {
+    require!(target > 0, EscrowError::InvalidTarget);
+
+    require!(
+        funding_deadline > Clock::get()?.unix_timestamp,
+        EscrowError::InvalidFundingDeadline
+    );
+
+    let mut bump = order.bump.unwrap();
+    let mut order = order.account.clone();
+    let mut profile_bump = seller_profile.bump.unwrap();
+    let mut seller_profile = seller_profile.account.clone();
+
+    let mut order_id = open_order(&seller, &config, &order, &seller_profile, profile_bump)?;
+
+    let mut vault = vault.account.clone();
+
+    assign!(
+        order.borrow_mut().seller_token_account,
+        seller_token_account.key()
+    );
+
+    assign!(order.borrow_mut().mint, mint.key());
+
+    assign!(order.borrow_mut().vault, vault.key());
+
+    // the order amount is the target the contributions have to reach
+    assign!(order.borrow_mut().amount, target);
+
+    assign!(order.borrow_mut().funding_deadline, funding_deadline);
+
+    assign!(order.borrow_mut().mode, PaymentMode::Crowdfund);
+
+    assign!(order.borrow_mut().bump, bump);
+
+    assign!(order.borrow_mut().state, OrderState::Pending);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: target,
+        old_state: None,
+        new_state: OrderState::Pending,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+pub fn init_native_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut config: SeahorseAccount<'info, '_, Config>,
+    mut order: Empty<Mutable<LoadedEscrowOrder<'info, '_>>>,
+    mut seller_profile: Empty<Mutable<LoadedSellerProfile<'info, '_>>>,
+    mut referee: Pubkey,
+    mut amount: u64,
+    mut commission: u16,
+    mut release_window: i64,
+    mut dispute_window: i64,
+    mut dispute_fallback: DisputeFallback,
+    mut designated_buyer: Option<Pubkey>,
+) -> Result<()> {
+    require!(commission <= 10000, EscrowError::CommissionTooHigh);
+
+    require!(release_window > 0, EscrowError::InvalidReleaseWindow);
+
+    require!(dispute_window > 0, EscrowError::InvalidDisputeWindow);
+
+    let mut bump = order.bump.unwrap();
+    let mut order = order.account.clone();
+    let mut profile_bump = seller_profile.bump.unwrap();
+    let mut seller_profile = seller_profile.account.clone();
+
+    let mut order_id = open_order(&seller, &config, &order, &seller_profile, profile_bump)?;
+
+    assign!(order.borrow_mut().referee, referee);
+
+    assign!(order.borrow_mut().amount, amount);
+
+    assign!(order.borrow_mut().commission, commission);
+
+    assign!(order.borrow_mut().release_window, release_window);
+
+    assign!(order.borrow_mut().dispute_window, dispute_window);
+
+    assign!(order.borrow_mut().dispute_fallback, dispute_fallback);
+
+    assign!(order.borrow_mut().designated_buyer, designated_buyer);
+
+    assign!(order.borrow_mut().mode, PaymentMode::Native);
+
+    assign!(order.borrow_mut().bump, bump);
+
+    assign!(order.borrow_mut().state, OrderState::Pending);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: amount,
+        old_state: None,
+        new_state: OrderState::Pending,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn init_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut config: SeahorseAccount<'info, '_, Config>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut order: Empty<Mutable<LoadedEscrowOrder<'info, '_>>>,
+    mut seller_profile: Empty<Mutable<LoadedSellerProfile<'info, '_>>>,
+    mut vault: Empty<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut bond_vault: Option<Empty<SeahorseInterfaceAccount<'info, '_, TokenAccount>>>,
+    mut referee: Pubkey,
+    mut amount: u64,
+    mut commission: u16,
+    mut release_window: i64,
+    mut dispute_window: i64,
+    mut dispute_fallback: DisputeFallback,
+    mut designated_buyer: Option<Pubkey>,
+    mut usd_price: Option<UsdPrice>,
+    mut milestones: Vec<u64>,
+    mut bond: u64,
+) -> Result<()> {
+    require!(commission <= 10000, EscrowError::CommissionTooHigh);
+
+    require!(
+        milestones.len() <= MAX_MILESTONES,
+        EscrowError::InvalidMilestones
+    );
+
+    // milestones are fixed token amounts, which a USD amount only becomes
+    // on deposit
+    require!(
+        milestones.is_empty() || usd_price.is_none(),
+        EscrowError::InvalidMilestones
+    );
+
+    require!(
+        usd_price.is_none() || cfg!(feature = "usd-pricing"),
+        EscrowError::UsdPricingDisabled
+    );
+
+    require!(release_window > 0, EscrowError::InvalidReleaseWindow);
+
+    require!(dispute_window > 0, EscrowError::InvalidDisputeWindow);
+
+    // the bond vault is only created for orders with a bond
+    require!(
+        bond_vault.is_some() == (bond > 0),
+        EscrowError::WrongBondVault
+    );
+
+    let mut bump = order.bump.unwrap();
+    let mut order = order.account.clone();
+    let mut profile_bump = seller_profile.bump.unwrap();
+    let mut seller_profile = seller_profile.account.clone();
+
+    let mut order_id = open_order(&seller, &config, &order, &seller_profile, profile_bump)?;
+
+    let mut vault = vault.account.clone();
+    let mut bond_vault = bond_vault.map(|bond_vault| bond_vault.account.clone());
+
+    assign!(
+        order.borrow_mut().seller_token_account,
+        seller_token_account.key()
+    );
+
+    assign!(order.borrow_mut().mint, mint.key());
+
+    assign!(order.borrow_mut().vault, vault.key());
+
+    assign!(order.borrow_mut().referee, referee);
+
+    assign!(order.borrow_mut().amount, amount);
+
+    assign!(order.borrow_mut().commission, commission);
+
+    assign!(order.borrow_mut().release_window, release_window);
+
+    assign!(order.borrow_mut().dispute_window, dispute_window);
+
+    assign!(order.borrow_mut().dispute_fallback, dispute_fallback);
+
+    assign!(order.borrow_mut().designated_buyer, designated_buyer);
+
+    assign!(order.borrow_mut().usd_price, usd_price);
+
+    if !milestones.is_empty() {
+        let mut total = 0u64;
+        let mut stored = [0u64; MAX_MILESTONES];
+
+        for (index, milestone) in milestones.iter().enumerate() {
+            require!(*milestone > 0, EscrowError::InvalidMilestones);
+
+            total = total
+                .checked_add(*milestone)
+                .ok_or(EscrowError::MathOverflow)?;
+
+            stored[index] = *milestone;
+        }
+
+        require!(total == amount, EscrowError::InvalidMilestones);
+
+        assign!(order.borrow_mut().milestones, stored);
+
+        assign!(order.borrow_mut().milestone_count, milestones.len() as u8);
+    }
+
+    // the seller locks the bond up front, so breaching the order costs more
+    // than the commission alone
+    if let Some(bond_vault) = &bond_vault {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                seller_token_account.programs.get("token_program"),
+                token_interface::TransferChecked {
+                    from: seller_token_account.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: bond_vault.clone().to_account_info(),
+                    authority: seller.clone().to_account_info(),
+                },
+            ),
+            bond,
+            mint.decimals,
+        )?;
+
+        let mut fee = transfer_fee(&mint.to_account_info(), bond)?;
+
+        assign!(
+            order.borrow_mut().bond,
+            bond.checked_sub(fee).ok_or(EscrowError::MathOverflow)?
+        );
+    }
+
+    assign!(order.borrow_mut().mode, PaymentMode::Token);
+
+    assign!(order.borrow_mut().bump, bump);
+
+    assign!(order.borrow_mut().state, OrderState::Pending);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: amount,
+        old_state: None,
+        new_state: OrderState::Pending,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn init_swap_order_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut config: SeahorseAccount<'info, '_, Config>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut seller_item_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut item_mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut order: Empty<Mutable<LoadedEscrowOrder<'info, '_>>>,
+    mut seller_profile: Empty<Mutable<LoadedSellerProfile<'info, '_>>>,
+    mut vault: Empty<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut item_vault: Empty<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut amount: u64,
+    mut item_amount: u64,
+    mut designated_buyer: Option<Pubkey>,
+) -> Result<()> {
+    require!(item_amount > 0, EscrowError::InvalidItemAmount);
+
+    let mut bump = order.bump.unwrap();
+    let mut order = order.account.clone();
+    let mut profile_bump = seller_profile.bump.unwrap();
+    let mut seller_profile = seller_profile.account.clone();
+
+    let mut order_id = open_order(&seller, &config, &order, &seller_profile, profile_bump)?;
+
+    let mut vault = vault.account.clone();
+    let mut item_vault = item_vault.account.clone();
+
+    // the seller escrows the item up front, so the buyer's deposit can
+    // settle the trade on its own
+    token_interface::transfer_checked(
+        CpiContext::new(
+            seller_item_account.programs.get("item_token_program"),
+            token_interface::TransferChecked {
+                from: seller_item_account.to_account_info(),
+                mint: item_mint.to_account_info(),
+                to: item_vault.clone().to_account_info(),
+                authority: seller.clone().to_account_info(),
+            },
+        ),
+        item_amount,
+        item_mint.decimals,
+    )?;
+
+    let mut fee = transfer_fee(&item_mint.to_account_info(), item_amount)?;
+
+    assign!(
+        order.borrow_mut().item_amount,
+        item_amount
+            .checked_sub(fee)
+            .ok_or(EscrowError::MathOverflow)?
+    );
+
+    assign!(
+        order.borrow_mut().seller_token_account,
+        seller_token_account.key()
+    );
+
+    assign!(order.borrow_mut().mint, mint.key());
+
+    assign!(order.borrow_mut().vault, vault.key());
+
+    assign!(order.borrow_mut().item_mint, item_mint.key());
+
+    assign!(order.borrow_mut().item_vault, item_vault.key());
+
+    assign!(order.borrow_mut().amount, amount);
+
+    assign!(order.borrow_mut().designated_buyer, designated_buyer);
+
+    assign!(order.borrow_mut().mode, PaymentMode::Swap);
+
+    assign!(order.borrow_mut().bump, bump);
+
+    assign!(order.borrow_mut().state, OrderState::Pending);
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: amount,
+        old_state: None,
+        new_state: OrderState::Pending,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+pub fn initialize_config_handler<'info>(
+    mut admin: SeahorseSigner<'info, '_>,
+    mut config: Empty<Mutable<LoadedConfig<'info, '_>>>,
+    mut fee_bps: u16,
+    mut treasury: Pubkey,
+) -> Result<()> {
+    require!(fee_bps <= 10000, EscrowError::FeeTooHigh);
+
+    let mut bump = config.bump.unwrap();
+    let mut config = config.account.clone();
+
+    assign!(config.borrow_mut().admin, admin.key());
+
+    assign!(config.borrow_mut().fee_bps, fee_bps);
+
+    assign!(config.borrow_mut().treasury, treasury);
+
+    assign!(config.borrow_mut().bump, bump);
+
+    Ok(())
+}
+
+pub fn mutual_cancel_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited
+            || order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // both parties agreed, so the referee takes no commission
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: buyer_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.key().as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        deposited_amount,
+        mint.decimals,
+    )?;
+
+    let mut bond = order.borrow().bond;
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Refunded);
+
+    track_state(&seller_profile, OrderState::Refunded)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// The new admin only takes over once they accept, so a mistyped key
+// cannot lock the config
+pub fn propose_admin_handler<'info>(
+    mut admin: SeahorseSigner<'info, '_>,
+    mut config: Mutable<LoadedConfig<'info, '_>>,
+    mut new_admin: Pubkey,
+) -> Result<()> {
+    assign!(config.borrow_mut().pending_admin, Some(new_admin));
+
+    Ok(())
+}
+
+pub fn reclaim_contribution_handler<'info>(
+    mut contributor: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut contribution: Mutable<LoadedContribution<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut contributor_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Crowdfund,
+        EscrowError::WrongPaymentMode
+    );
+
+    // an order that reached its target stays with the seller
+    require!(
+        order.borrow().state == OrderState::Pending,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        Clock::get()?.unix_timestamp >= order.borrow().funding_deadline,
+        EscrowError::FundingOpen
+    );
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut amount = contribution.borrow().amount;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: contributor_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    assign!(
+        order.borrow_mut().deposited_amount,
+        order
+            .borrow()
+            .deposited_amount
+            .checked_sub(amount)
+            .ok_or(EscrowError::MathOverflow)?
+    );
+
+    assign!(contribution.borrow_mut().amount, 0);
+
+    Ok(())
+}
+pub fn refund_handler<'info>(
+    mut referee: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut referee_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // commission is rounded down, any remainder goes to the buyer
+    let mut fee = ((deposited_amount as u128)
+        .checked_mul(order.borrow().commission as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut payout = deposited_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: referee_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        fee,
+        mint.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: buyer_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        payout,
+        mint.decimals,
+    )?;
+
+    // a ruling for the buyer forfeits the seller bond to them
+    let mut bond = order.borrow().bond;
+
+    pay_bond(&order, &mint, &bond_vault, &buyer_token_account, bond)?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Refunded);
+
+    track_state(&seller_profile, OrderState::Refunded)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: referee.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn refund_native_handler<'info>(
+    mut referee: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut buyer: WithPrograms<'info, '_, SystemAccount<'info>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // commission is rounded down, any remainder goes to the buyer
+    let mut fee = ((deposited_amount as u128)
+        .checked_mul(order.borrow().commission as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut payout = deposited_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    pay_lamports(
+        &order.borrow().__account__.to_account_info(),
+        &referee.to_account_info(),
+        fee,
+    )?;
+
+    pay_lamports(
+        &order.borrow().__account__.to_account_info(),
+        &buyer.to_account_info(),
+        payout,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Refunded);
+
+    track_state(&seller_profile, OrderState::Refunded)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: referee.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn release_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    // checked after the state, so a release before any deposit reports
+    // InvalidState rather than NotBuyer
+    require!(buyer.key() == order.borrow().buyer, EscrowError::NotBuyer);
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        deposited_amount,
+    )?;
+
+    let mut bond = order.borrow().bond;
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn release_crowdfund_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().mode == PaymentMode::Crowdfund,
+        EscrowError::WrongPaymentMode
+    );
+
+    // the order only reaches Deposited once the target is met
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        deposited_amount,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+pub fn release_milestone_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut index: u8,
+) -> Result<()> {
+    // a dispute moves the order out of Deposited, which freezes every
+    // milestone not yet released
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        index < order.borrow().milestone_count,
+        EscrowError::InvalidMilestone
+    );
+
+    let mut flag = 1u8 << index;
+
+    require!(
+        order.borrow().released_milestones & flag == 0,
+        EscrowError::MilestoneReleased
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+    let mut released_milestones = order.borrow().released_milestones | flag;
+    let mut last = released_milestones.count_ones() == order.borrow().milestone_count as u32;
+
+    // the last milestone takes whatever is still held, so a transfer fee
+    // taken on deposit comes out of it
+    let mut payout = if last {
+        deposited_amount
+    } else {
+        order.borrow().milestones[index as usize].min(deposited_amount)
+    };
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        payout,
+    )?;
+
+    assign!(order.borrow_mut().released_milestones, released_milestones);
+
+    // later payouts, including a refund or ruling after a dispute, only
+    // see what is still held
+    assign!(
+        order.borrow_mut().deposited_amount,
+        deposited_amount - payout
+    );
+
+    let mut old_state = order.borrow().state;
+    let mut new_state = old_state;
+
+    if last {
+        let mut bond = order.borrow().bond;
+
+        pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+        new_state = OrderState::Settled;
+
+        assign!(order.borrow_mut().state, new_state);
+
+        track_state(&seller_profile, new_state)?;
+    }
+
+    // earlier milestones leave the order Deposited, but are still reported
+    // so indexers see every payout
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: payout,
+        old_state: Some(old_state),
+        new_state: new_state,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn release_native_handler<'info>(
+    mut buyer: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut seller: WithPrograms<'info, '_, SystemAccount<'info>>,
+    mut treasury: WithPrograms<'info, '_, SystemAccount<'info>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    pay_seller_native(
+        &order,
+        &seller.to_account_info(),
+        &treasury.to_account_info(),
+        deposited_amount,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Settled);
+
+    track_state(&seller_profile, OrderState::Settled)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: buyer.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Settled,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn resolve_handler<'info>(
+    mut referee: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut referee_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // commission is rounded down, any remainder goes to the seller
+    let mut fee = ((deposited_amount as u128)
+        .checked_mul(order.borrow().commission as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut payout = deposited_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: referee_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        fee,
+        mint.decimals,
+    )?;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        payout,
+    )?;
+
+    // a ruling for the seller hands the bond back
+    let mut bond = order.borrow().bond;
+
+    pay_bond(&order, &mint, &bond_vault, &seller_token_account, bond)?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Resolved);
+
+    track_state(&seller_profile, OrderState::Resolved)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: referee.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Resolved,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn resolve_native_handler<'info>(
+    mut referee: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut seller: WithPrograms<'info, '_, SystemAccount<'info>>,
+    mut treasury: WithPrograms<'info, '_, SystemAccount<'info>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    require!(
+        order.borrow().mode == PaymentMode::Native,
+        EscrowError::WrongPaymentMode
+    );
+
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // commission is rounded down, any remainder goes to the seller
+    let mut fee = ((deposited_amount as u128)
+        .checked_mul(order.borrow().commission as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut payout = deposited_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    pay_lamports(
+        &order.borrow().__account__.to_account_info(),
+        &referee.to_account_info(),
+        fee,
+    )?;
+
+    pay_seller_native(
+        &order,
+        &seller.to_account_info(),
+        &treasury.to_account_info(),
+        payout,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Resolved);
+
+    track_state(&seller_profile, OrderState::Resolved)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: referee.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Resolved,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn rule_handler<'info>(
+    mut referee: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut referee_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut buyer_share: u16,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    require!(buyer_share <= 10000, EscrowError::InvalidRuling);
+
+    let mut seller = order.borrow().seller;
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // commission comes off the top as in refund and resolve
+    let mut fee = ((deposited_amount as u128)
+        .checked_mul(order.borrow().commission as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut payout = deposited_amount
+        .checked_sub(fee)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    // the buyer share is rounded down, any remainder goes to the seller
+    let mut buyer_payout = ((payout as u128)
+        .checked_mul(buyer_share as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    let mut seller_payout = payout - buyer_payout;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: referee_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        fee,
+        mint.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: buyer_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        buyer_payout,
+        mint.decimals,
+    )?;
+
+    pay_seller(
+        &order,
+        &mint,
+        &vault,
+        &seller_token_account,
+        &treasury_token_account,
+        seller_payout,
+    )?;
+
+    // the bond follows the same split, without a commission
+    let mut bond = order.borrow().bond;
+
+    let mut buyer_bond = ((bond as u128)
+        .checked_mul(buyer_share as u128)
+        .ok_or(EscrowError::MathOverflow)?
+        / 10000) as u64;
+
+    pay_bond(&order, &mint, &bond_vault, &buyer_token_account, buyer_bond)?;
+
+    pay_bond(
+        &order,
+        &mint,
+        &bond_vault,
+        &seller_token_account,
+        bond - buyer_bond,
+    )?;
+
+    assign!(order.borrow_mut().ruling, Some(buyer_share));
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Resolved);
+
+    track_state(&seller_profile, OrderState::Resolved)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: referee.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Resolved,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn seller_dispute_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    // a crowdfund has no buyer or referee to settle a dispute with
+    require!(
+        order.borrow().mode != PaymentMode::Crowdfund,
+        EscrowError::WrongPaymentMode
+    );
+
+    assign!(order.borrow_mut().disputer, seller.key());
+
+    // anyone can apply the dispute fallback once this passes
+    let mut dispute_deadline = Clock::get()?
+        .unix_timestamp
+        .checked_add(order.borrow().dispute_window)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    assign!(order.borrow_mut().dispute_deadline, dispute_deadline);
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Dispute);
+
+    track_state(&seller_profile, OrderState::Dispute)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: order.borrow().amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Dispute,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn seller_refund_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut seller_profile: Mutable<LoadedSellerProfile<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut bond_vault: Option<SeahorseInterfaceAccount<'info, '_, TokenAccount>>,
+    mut buyer_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut treasury_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut amount: Option<u64>,
+) -> Result<()> {
+    require!(
+        order.borrow().state == OrderState::Deposited
+            || order.borrow().state == OrderState::Dispute,
+        EscrowError::InvalidState
+    );
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+    let mut deposited_amount = order.borrow().deposited_amount;
+
+    // without an amount the whole deposit goes back to the buyer, otherwise
+    // the rest is released to the seller
+    let mut refund = amount.unwrap_or(deposited_amount);
+
+    require!(refund > 0, EscrowError::InvalidRefund);
+
+    require!(refund <= deposited_amount, EscrowError::RefundTooLarge);
+
+    // splitting a disputed deposit is for the referee to decide with rule
+    require!(
+        refund == deposited_amount || order.borrow().state == OrderState::Deposited,
+        EscrowError::InvalidState
+    );
+
+    let mut payout = deposited_amount - refund;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: buyer_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.key().as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        refund,
+        mint.decimals,
+    )?;
+
+    if payout > 0 {
+        pay_seller(
+            &order,
+            &mint,
+            &vault,
+            &seller_token_account,
+            &treasury_token_account,
+            payout,
+        )?;
+    }
+
+    // a disputed order forfeits the whole bond to the buyer, a partial refund
+    // forfeits the refunded share and only a full voluntary refund gets the
+    // bond back
+    let mut bond = order.borrow().bond;
+    let mut forfeit = if order.borrow().state == OrderState::Dispute {
+        bond
+    } else if refund == deposited_amount {
+        0
+    } else {
+        ((bond as u128) * (refund as u128) / (deposited_amount as u128)) as u64
+    };
+
+    pay_bond(&order, &mint, &bond_vault, &buyer_token_account, forfeit)?;
+
+    pay_bond(
+        &order,
+        &mint,
+        &bond_vault,
+        &seller_token_account,
+        bond - forfeit,
+    )?;
+
+    let mut old_state = order.borrow().state;
+
+    assign!(order.borrow_mut().state, OrderState::Refunded);
+
+    track_state(&seller_profile, OrderState::Refunded)?;
+
+    emit!(OrderStateChanged {
+        order: order.borrow().__account__.key(),
+        actor: seller.key(),
+        mint: order.borrow().mint,
+        amount: deposited_amount,
+        old_state: Some(old_state),
+        new_state: OrderState::Refunded,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn set_fee_handler<'info>(
+    mut admin: SeahorseSigner<'info, '_>,
+    mut config: Mutable<LoadedConfig<'info, '_>>,
+    mut fee_bps: u16,
+) -> Result<()> {
+    require!(fee_bps <= 10000, EscrowError::FeeTooHigh);
+
+    assign!(config.borrow_mut().fee_bps, fee_bps);
+
+    Ok(())
+}
+
+pub fn set_paused_handler<'info>(
+    mut admin: SeahorseSigner<'info, '_>,
+    mut config: Mutable<LoadedConfig<'info, '_>>,
+    mut paused: bool,
+) -> Result<()> {
+    assign!(config.borrow_mut().paused, paused);
+
+    Ok(())
+}
+
+pub fn set_treasury_handler<'info>(
+    mut admin: SeahorseSigner<'info, '_>,
+    mut config: Mutable<LoadedConfig<'info, '_>>,
+    mut treasury: Pubkey,
+) -> Result<()> {
+    assign!(config.borrow_mut().treasury, treasury);
+
+    Ok(())
+}
+
+pub fn sweep_excess_handler<'info>(
+    mut seller: SeahorseSigner<'info, '_>,
+    mut order: Mutable<LoadedEscrowOrder<'info, '_>>,
+    mut mint: SeahorseInterfaceAccount<'info, '_, Mint>,
+    mut vault: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+    mut seller_token_account: SeahorseInterfaceAccount<'info, '_, TokenAccount>,
+) -> Result<()> {
+    // only an open deposit is owed to the buyer or seller, anything else
+    // in the vault was sent to it directly and goes back to the seller. The
+    // contributions of a crowdfund order that is still funding count as an
+    // open deposit
+    let mut locked = if order.borrow().state == OrderState::Deposited
+        || order.borrow().state == OrderState::Dispute
+        || (order.borrow().mode == PaymentMode::Crowdfund
+            && order.borrow().state == OrderState::Pending)
+    {
+        order.borrow().deposited_amount
+    } else {
+        0
+    };
+
+    let mut excess = vault
+        .amount
+        .checked_sub(locked)
+        .ok_or(EscrowError::MathOverflow)?;
+
+    require!(excess > 0, EscrowError::NothingToSweep);
+
+    let mut order_id = order.borrow().order_id;
+    let mut bump = order.borrow().bump;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            vault.programs.get("token_program"),
+            token_interface::TransferChecked {
+                from: vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: seller_token_account.clone().to_account_info(),
+                authority: order.borrow().__account__.to_account_info(),
+            },
+            &[Mutable::new(vec![
+                "order".to_string().as_bytes().as_ref(),
+                seller.key().as_ref(),
+                order_id.to_le_bytes().as_ref(),
+                bump.to_le_bytes().as_ref(),
+            ])
+            .borrow()
+            .as_slice()],
+        ),
+        excess,
+        mint.decimals,
+    )?;
+
+    Ok(())
 }
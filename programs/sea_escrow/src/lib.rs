@@ -7,7 +7,7 @@ pub mod dot;
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::{self, AssociatedToken},
-    token::{self, Mint, Token, TokenAccount},
+    token_interface::{self, Mint, TokenAccount, TokenInterface},
 };
 
 use dot::program::*;
@@ -151,6 +151,9 @@ pub mod seahorse_util {
     pub type SeahorseAccount<'info, 'entrypoint, A> =
         WithPrograms<'info, 'entrypoint, Box<Account<'info, A>>>;
 
+    pub type SeahorseInterfaceAccount<'info, 'entrypoint, A> =
+        WithPrograms<'info, 'entrypoint, Box<InterfaceAccount<'info, A>>>;
+
     pub type SeahorseSigner<'info, 'entrypoint> = WithPrograms<'info, 'entrypoint, Signer<'info>>;
 
     #[derive(Clone, Debug)]
@@ -224,19 +227,50 @@ mod sea_escrow {
     use std::collections::HashMap;
 
     #[derive(Accounts)]
-    pub struct Deposit<'info> {
+    pub struct AcceptAdmin<'info> {
         #[account(mut)]
-        pub buyer: Signer<'info>,
+        pub new_admin: Signer<'info>,
+        # [account (mut , seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = config . pending_admin == Some (new_admin . key ()) @ EscrowError :: NotPendingAdmin)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let new_admin = SeahorseSigner {
+            account: &ctx.accounts.new_admin,
+            programs: &programs_map,
+        };
+
+        let config = dot::program::Config::load(&mut ctx.accounts.config, &programs_map);
+
+        accept_admin_handler(new_admin.clone(), config.clone())?;
+
+        dot::program::Config::store(config);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct CancelOrder<'info> {
         #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , close = seller , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
         pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
         #[account(mut)]
-        pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
         #[account(mut)]
-        pub vault: Box<Account<'info, TokenAccount>>,
-        pub token_program: Program<'info, Token>,
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
     }
 
-    pub fn deposit(ctx: Context<Deposit>) -> Result<()> {
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
         let mut programs = HashMap::new();
 
         programs.insert(
@@ -245,89 +279,235 @@ mod sea_escrow {
         );
 
         let programs_map = ProgramsMap(programs);
-        let buyer = SeahorseSigner {
-            account: &ctx.accounts.buyer,
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
             programs: &programs_map,
         };
 
         let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
-        let buyer_token_account = SeahorseAccount {
-            account: &ctx.accounts.buyer_token_account,
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
             programs: &programs_map,
         };
 
-        let vault = SeahorseAccount {
+        let vault = SeahorseInterfaceAccount {
             account: &ctx.accounts.vault,
             programs: &programs_map,
         };
 
-        deposit_handler(
-            buyer.clone(),
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        cancel_order_handler(
+            seller.clone(),
             order.clone(),
-            buyer_token_account.clone(),
+            seller_profile.clone(),
+            mint.clone(),
             vault.clone(),
-        );
+            bond_vault.clone(),
+            seller_token_account.clone(),
+        )?;
 
         dot::program::EscrowOrder::store(order);
 
+        dot::program::SellerProfile::store(seller_profile);
+
         return Ok(());
     }
 
     #[derive(Accounts)]
-    pub struct Dispute<'info> {
-        #[account(mut)]
-        pub buyer: Signer<'info>,
+    pub struct ClaimAfterDisputeTimeout<'info> {
         #[account(mut)]
+        pub caller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = buyer_token_account @ EscrowError :: WrongPayoutAccount , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
         pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
     }
 
-    pub fn dispute(ctx: Context<Dispute>) -> Result<()> {
+    pub fn claim_after_dispute_timeout(ctx: Context<ClaimAfterDisputeTimeout>) -> Result<()> {
         let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
         let programs_map = ProgramsMap(programs);
-        let buyer = SeahorseSigner {
-            account: &ctx.accounts.buyer,
+        let caller = SeahorseSigner {
+            account: &ctx.accounts.caller,
             programs: &programs_map,
         };
 
         let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
 
-        dispute_handler(buyer.clone(), order.clone());
+        claim_after_dispute_timeout_handler(
+            caller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            buyer_token_account.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+        )?;
 
         dot::program::EscrowOrder::store(order);
 
+        dot::program::SellerProfile::store(seller_profile);
+
         return Ok(());
     }
 
     #[derive(Accounts)]
-    # [instruction (order_id : u16 , referee : Pubkey , amount : u64)]
-    pub struct InitOrder<'info> {
+    pub struct ClaimAfterDisputeTimeoutNative<'info> {
         #[account(mut)]
-        pub seller: Signer<'info>,
+        pub caller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = buyer @ EscrowError :: WrongPayoutAccount , has_one = seller @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
         #[account(mut)]
-        pub seller_token_account: Box<Account<'info, TokenAccount>>,
+        pub buyer: SystemAccount<'info>,
         #[account(mut)]
-        pub mint: Box<Account<'info, Mint>>,
-        # [account (init , space = std :: mem :: size_of :: < dot :: program :: EscrowOrder > () + 8 , payer = seller , seeds = ["order" . as_bytes () . as_ref () , seller . key () . as_ref () , order_id . to_le_bytes () . as_ref ()] , bump)]
-        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
-        # [account (init , payer = seller , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = mint , token :: authority = order)]
-        pub vault: Box<Account<'info, TokenAccount>>,
-        pub rent: Sysvar<'info, Rent>,
-        pub system_program: Program<'info, System>,
-        pub token_program: Program<'info, Token>,
+        pub seller: SystemAccount<'info>,
+        # [account (mut , address = config . treasury)]
+        pub treasury: SystemAccount<'info>,
     }
 
-    pub fn init_order(
-        ctx: Context<InitOrder>,
-        order_id: u16,
-        referee: Pubkey,
-        amount: u64,
+    pub fn claim_after_dispute_timeout_native(
+        ctx: Context<ClaimAfterDisputeTimeoutNative>,
     ) -> Result<()> {
         let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let caller = SeahorseSigner {
+            account: &ctx.accounts.caller,
+            programs: &programs_map,
+        };
 
-        programs.insert(
-            "system_program",
-            ctx.accounts.system_program.to_account_info(),
-        );
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let buyer = WithPrograms {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let seller = WithPrograms {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let treasury = WithPrograms {
+            account: &ctx.accounts.treasury,
+            programs: &programs_map,
+        };
+
+        claim_after_dispute_timeout_native_handler(
+            caller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            buyer.clone(),
+            seller.clone(),
+            treasury.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ClaimAfterTimeout<'info> {
+        #[account(mut)]
+        pub caller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn claim_after_timeout(ctx: Context<ClaimAfterTimeout>) -> Result<()> {
+        let mut programs = HashMap::new();
 
         programs.insert(
             "token_program",
@@ -335,64 +515,161 @@ mod sea_escrow {
         );
 
         let programs_map = ProgramsMap(programs);
-        let seller = SeahorseSigner {
-            account: &ctx.accounts.seller,
+        let caller = SeahorseSigner {
+            account: &ctx.accounts.caller,
             programs: &programs_map,
         };
 
-        let seller_token_account = SeahorseAccount {
-            account: &ctx.accounts.seller_token_account,
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
             programs: &programs_map,
         };
 
-        let mint = SeahorseAccount {
-            account: &ctx.accounts.mint,
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
             programs: &programs_map,
         };
 
-        let order = Empty {
-            account: dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map),
-            bump: Some(ctx.bumps.order),
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
         };
 
-        let vault = Empty {
-            account: SeahorseAccount {
-                account: &ctx.accounts.vault,
-                programs: &programs_map,
-            },
-            bump: Some(ctx.bumps.vault),
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
         };
 
-        init_order_handler(
-            seller.clone(),
-            seller_token_account.clone(),
-            mint.clone(),
+        claim_after_timeout_handler(
+            caller.clone(),
             order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
             vault.clone(),
-            order_id,
-            referee,
-            amount,
-        );
+            bond_vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+        )?;
 
-        dot::program::EscrowOrder::store(order.account);
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
 
         return Ok(());
     }
 
     #[derive(Accounts)]
-    pub struct Refund<'info> {
-        #[account(mut)]
-        pub referee: Signer<'info>,
+    pub struct ClaimAfterTimeoutNative<'info> {
         #[account(mut)]
+        pub caller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: WrongPayoutAccount)]
         pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
         #[account(mut)]
-        pub vault: Box<Account<'info, TokenAccount>>,
+        pub seller: SystemAccount<'info>,
+        # [account (mut , address = config . treasury)]
+        pub treasury: SystemAccount<'info>,
+    }
+
+    pub fn claim_after_timeout_native(ctx: Context<ClaimAfterTimeoutNative>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let caller = SeahorseSigner {
+            account: &ctx.accounts.caller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let seller = WithPrograms {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let treasury = WithPrograms {
+            account: &ctx.accounts.treasury,
+            programs: &programs_map,
+        };
+
+        claim_after_timeout_native_handler(
+            caller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            seller.clone(),
+            treasury.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct CloseContribution<'info> {
         #[account(mut)]
-        pub buyer_token_account: Box<Account<'info, TokenAccount>>,
-        pub token_program: Program<'info, Token>,
+        pub contributor: Signer<'info>,
+        /// CHECK: bound to the receipt by its seeds, and may already be closed
+        pub order: UncheckedAccount<'info>,
+        # [account (mut , close = contributor , seeds = ["contribution" . as_bytes () . as_ref () , order . key () . as_ref () , contributor . key () . as_ref ()] , bump = contribution . bump)]
+        pub contribution: Box<Account<'info, dot::program::Contribution>>,
     }
 
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+    pub fn close_contribution(ctx: Context<CloseContribution>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let contributor = SeahorseSigner {
+            account: &ctx.accounts.contributor,
+            programs: &programs_map,
+        };
+
+        let contribution =
+            dot::program::Contribution::load(&mut ctx.accounts.contribution, &programs_map);
+
+        close_contribution_handler(
+            contributor.clone(),
+            ctx.accounts.order.clone(),
+            contribution.clone(),
+        )?;
+
+        dot::program::Contribution::store(contribution);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct CloseCrowdfundOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , close = seller , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        #[account(mut)]
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn close_crowdfund_order(ctx: Context<CloseCrowdfundOrder>) -> Result<()> {
         let mut programs = HashMap::new();
 
         programs.insert(
@@ -401,48 +678,88 @@ mod sea_escrow {
         );
 
         let programs_map = ProgramsMap(programs);
-        let referee = SeahorseSigner {
-            account: &ctx.accounts.referee,
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
             programs: &programs_map,
         };
 
         let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
-        let vault = SeahorseAccount {
-            account: &ctx.accounts.vault,
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
             programs: &programs_map,
         };
 
-        let buyer_token_account = SeahorseAccount {
-            account: &ctx.accounts.buyer_token_account,
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
             programs: &programs_map,
         };
 
-        refund_handler(
-            referee.clone(),
+        close_crowdfund_order_handler(
+            seller.clone(),
             order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
             vault.clone(),
-            buyer_token_account.clone(),
-        );
+        )?;
 
         dot::program::EscrowOrder::store(order);
 
+        dot::program::SellerProfile::store(seller_profile);
+
         return Ok(());
     }
 
     #[derive(Accounts)]
-    pub struct Release<'info> {
+    pub struct CloseNativeOrder<'info> {
         #[account(mut)]
-        pub buyer: Signer<'info>,
+        pub seller: Signer<'info>,
+        # [account (mut , close = seller , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+    }
+
+    pub fn close_native_order(ctx: Context<CloseNativeOrder>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+
+        close_native_order_handler(seller.clone(), order.clone(), seller_profile.clone())?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct CloseOrder<'info> {
         #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , close = seller , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
         pub order: Box<Account<'info, dot::program::EscrowOrder>>,
         #[account(mut)]
-        pub vault: Box<Account<'info, TokenAccount>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
         #[account(mut)]
-        pub seller_token_account: Box<Account<'info, TokenAccount>>,
-        pub token_program: Program<'info, Token>,
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
     }
 
-    pub fn release(ctx: Context<Release>) -> Result<()> {
+    pub fn close_order(ctx: Context<CloseOrder>) -> Result<()> {
         let mut programs = HashMap::new();
 
         programs.insert(
@@ -451,29 +768,2103 @@ mod sea_escrow {
         );
 
         let programs_map = ProgramsMap(programs);
-        let buyer = SeahorseSigner {
-            account: &ctx.accounts.buyer,
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
             programs: &programs_map,
         };
 
         let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
-        let vault = SeahorseAccount {
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
             account: &ctx.accounts.vault,
             programs: &programs_map,
         };
 
-        let seller_token_account = SeahorseAccount {
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
             account: &ctx.accounts.seller_token_account,
             programs: &programs_map,
         };
 
-        release_handler(
+        close_order_handler(
+            seller.clone(),
+            order.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            seller_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct CloseSwapOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , close = seller , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = item_mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = item_vault @ EscrowError :: WrongVault)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        #[account(mut)]
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        #[account(mut)]
+        pub item_mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["item" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub item_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = item_mint , token :: authority = seller , token :: token_program = item_token_program)]
+        pub seller_item_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub item_token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn close_swap_order(ctx: Context<CloseSwapOrder>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        programs.insert(
+            "item_token_program",
+            ctx.accounts.item_token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let item_mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.item_mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let item_vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.item_vault,
+            programs: &programs_map,
+        };
+
+        let seller_item_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_item_account,
+            programs: &programs_map,
+        };
+
+        close_swap_order_handler(
+            seller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            item_mint.clone(),
+            vault.clone(),
+            item_vault.clone(),
+            seller_item_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (amount : u64)]
+    pub struct Contribute<'info> {
+        #[account(mut)]
+        pub contributor: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = contributor)]
+        pub contributor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (init_if_needed , space = std :: mem :: size_of :: < dot :: program :: Contribution > () + 8 , payer = contributor , seeds = ["contribution" . as_bytes () . as_ref () , order . key () . as_ref () , contributor . key () . as_ref ()] , bump)]
+        pub contribution: Box<Account<'info, dot::program::Contribution>>,
+        pub system_program: Program<'info, System>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn contribute(ctx: Context<Contribute>, amount: u64) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let contributor = SeahorseSigner {
+            account: &ctx.accounts.contributor,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let contributor_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.contributor_token_account,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let contribution = Empty {
+            account: dot::program::Contribution::load(
+                &mut ctx.accounts.contribution,
+                &programs_map,
+            ),
+            bump: Some(ctx.bumps.contribution),
+        };
+
+        contribute_handler(
+            contributor.clone(),
+            order.clone(),
+            mint.clone(),
+            contributor_token_account.clone(),
+            vault.clone(),
+            contribution.clone(),
+            amount,
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::Contribution::store(contribution.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Deposit<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = buyer)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn deposit(ctx: Context<Deposit>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        deposit_handler(
+            buyer.clone(),
+            order.clone(),
+            mint.clone(),
+            buyer_token_account.clone(),
+            vault.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct DepositNative<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub system_program: Program<'info, System>,
+    }
+
+    pub fn deposit_native(ctx: Context<DepositNative>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+
+        deposit_native_handler(buyer.clone(), order.clone())?;
+
+        dot::program::EscrowOrder::store(order);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct DepositSwap<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = item_mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = item_vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        pub item_mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = buyer)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . item_mint , token :: authority = buyer , token :: token_program = item_token_program)]
+        pub buyer_item_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["item" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub item_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub item_token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn deposit_swap(ctx: Context<DepositSwap>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        programs.insert(
+            "item_token_program",
+            ctx.accounts.item_token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let item_mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.item_mint,
+            programs: &programs_map,
+        };
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let buyer_item_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_item_account,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let item_vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.item_vault,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        deposit_swap_handler(
+            buyer.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            item_mint.clone(),
+            buyer_token_account.clone(),
+            buyer_item_account.clone(),
+            vault.clone(),
+            item_vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct DepositUsd<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , constraint = order . usd_price . map (| p | p . price_feed) == Some (price_feed . key ()) @ EscrowError :: WrongPriceFeed)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        /// CHECK: must be the feed stored on the order, parsed as a Pyth price account
+        pub price_feed: UncheckedAccount<'info>,
+        # [account (mut , token :: mint = order . mint , token :: authority = buyer)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn deposit_usd(ctx: Context<DepositUsd>, max_amount: u64) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        deposit_usd_handler(
+            buyer.clone(),
+            order.clone(),
+            mint.clone(),
+            ctx.accounts.price_feed.clone(),
+            buyer_token_account.clone(),
+            vault.clone(),
+            max_amount,
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Dispute<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = buyer @ EscrowError :: NotBuyer)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+    }
+
+    pub fn dispute(ctx: Context<Dispute>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+
+        dispute_handler(buyer.clone(), order.clone(), seller_profile.clone())?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (target : u64 , funding_deadline : i64)]
+    pub struct InitCrowdfundOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (token :: mint = mint , token :: authority = seller)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (init_if_needed , space = std :: mem :: size_of :: < dot :: program :: SellerProfile > () + 8 , payer = seller , seeds = ["seller" . as_bytes () . as_ref () , seller . key () . as_ref ()] , bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (init , space = std :: mem :: size_of :: < dot :: program :: EscrowOrder > () + 8 , payer = seller , seeds = ["order" . as_bytes () . as_ref () , seller . key () . as_ref () , seller_profile . next_order_id . to_le_bytes () . as_ref ()] , bump)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (init , payer = seller , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = mint , token :: authority = order)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub rent: Sysvar<'info, Rent>,
+        pub system_program: Program<'info, System>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn init_crowdfund_order(
+        ctx: Context<InitCrowdfundOrder>,
+        target: u64,
+        funding_deadline: i64,
+    ) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let config = SeahorseAccount {
+            account: &ctx.accounts.config,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let order = Empty {
+            account: dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map),
+            bump: Some(ctx.bumps.order),
+        };
+
+        let seller_profile = Empty {
+            account: dot::program::SellerProfile::load(
+                &mut ctx.accounts.seller_profile,
+                &programs_map,
+            ),
+            bump: Some(ctx.bumps.seller_profile),
+        };
+
+        let vault = Empty {
+            account: SeahorseInterfaceAccount {
+                account: &ctx.accounts.vault,
+                programs: &programs_map,
+            },
+            bump: Some(ctx.bumps.vault),
+        };
+
+        init_crowdfund_order_handler(
+            seller.clone(),
+            config.clone(),
+            seller_token_account.clone(),
+            mint.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            vault.clone(),
+            target,
+            funding_deadline,
+        )?;
+
+        dot::program::EscrowOrder::store(order.account);
+
+        dot::program::SellerProfile::store(seller_profile.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (referee : Pubkey , amount : u64 , commission : u16 , release_window : i64 , dispute_window : i64 , dispute_fallback : DisputeFallback , designated_buyer : Option < Pubkey >)]
+    pub struct InitNativeOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (init_if_needed , space = std :: mem :: size_of :: < dot :: program :: SellerProfile > () + 8 , payer = seller , seeds = ["seller" . as_bytes () . as_ref () , seller . key () . as_ref ()] , bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (init , space = std :: mem :: size_of :: < dot :: program :: EscrowOrder > () + 8 , payer = seller , seeds = ["order" . as_bytes () . as_ref () , seller . key () . as_ref () , seller_profile . next_order_id . to_le_bytes () . as_ref ()] , bump)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub rent: Sysvar<'info, Rent>,
+        pub system_program: Program<'info, System>,
+    }
+
+    pub fn init_native_order(
+        ctx: Context<InitNativeOrder>,
+        referee: Pubkey,
+        amount: u64,
+        commission: u16,
+        release_window: i64,
+        dispute_window: i64,
+        dispute_fallback: DisputeFallback,
+        designated_buyer: Option<Pubkey>,
+    ) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let config = SeahorseAccount {
+            account: &ctx.accounts.config,
+            programs: &programs_map,
+        };
+
+        let order = Empty {
+            account: dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map),
+            bump: Some(ctx.bumps.order),
+        };
+
+        let seller_profile = Empty {
+            account: dot::program::SellerProfile::load(
+                &mut ctx.accounts.seller_profile,
+                &programs_map,
+            ),
+            bump: Some(ctx.bumps.seller_profile),
+        };
+
+        init_native_order_handler(
+            seller.clone(),
+            config.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            referee,
+            amount,
+            commission,
+            release_window,
+            dispute_window,
+            dispute_fallback,
+            designated_buyer,
+        )?;
+
+        dot::program::EscrowOrder::store(order.account);
+
+        dot::program::SellerProfile::store(seller_profile.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (referee : Pubkey , amount : u64 , commission : u16 , release_window : i64 , dispute_window : i64 , dispute_fallback : DisputeFallback , designated_buyer : Option < Pubkey > , usd_price : Option < UsdPrice > , milestones : Vec < u64 > , bond : u64)]
+    pub struct InitOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (mut , token :: mint = mint , token :: authority = seller)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (init_if_needed , space = std :: mem :: size_of :: < dot :: program :: SellerProfile > () + 8 , payer = seller , seeds = ["seller" . as_bytes () . as_ref () , seller . key () . as_ref ()] , bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (init , space = std :: mem :: size_of :: < dot :: program :: EscrowOrder > () + 8 , payer = seller , seeds = ["order" . as_bytes () . as_ref () , seller . key () . as_ref () , seller_profile . next_order_id . to_le_bytes () . as_ref ()] , bump)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (init , payer = seller , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = mint , token :: authority = order)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (init , payer = seller , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = mint , token :: authority = order)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        pub rent: Sysvar<'info, Rent>,
+        pub system_program: Program<'info, System>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn init_order(
+        ctx: Context<InitOrder>,
+        referee: Pubkey,
+        amount: u64,
+        commission: u16,
+        release_window: i64,
+        dispute_window: i64,
+        dispute_fallback: DisputeFallback,
+        designated_buyer: Option<Pubkey>,
+        usd_price: Option<UsdPrice>,
+        milestones: Vec<u64>,
+        bond: u64,
+    ) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let config = SeahorseAccount {
+            account: &ctx.accounts.config,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let order = Empty {
+            account: dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map),
+            bump: Some(ctx.bumps.order),
+        };
+
+        let seller_profile = Empty {
+            account: dot::program::SellerProfile::load(
+                &mut ctx.accounts.seller_profile,
+                &programs_map,
+            ),
+            bump: Some(ctx.bumps.seller_profile),
+        };
+
+        let vault = Empty {
+            account: SeahorseInterfaceAccount {
+                account: &ctx.accounts.vault,
+                programs: &programs_map,
+            },
+            bump: Some(ctx.bumps.vault),
+        };
+
+        let bond_vault = ctx.accounts.bond_vault.as_ref().map(|account| Empty {
+            account: SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            },
+            bump: Some(ctx.bumps.bond_vault),
+        });
+
+        init_order_handler(
+            seller.clone(),
+            config.clone(),
+            seller_token_account.clone(),
+            mint.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            referee,
+            amount,
+            commission,
+            release_window,
+            dispute_window,
+            dispute_fallback,
+            designated_buyer,
+            usd_price,
+            milestones,
+            bond,
+        )?;
+
+        dot::program::EscrowOrder::store(order.account);
+
+        dot::program::SellerProfile::store(seller_profile.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (amount : u64 , item_amount : u64 , designated_buyer : Option < Pubkey >)]
+    pub struct InitSwapOrder<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , constraint = ! config . paused @ EscrowError :: Paused)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (token :: mint = mint , token :: authority = seller)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , token :: mint = item_mint , token :: authority = seller , token :: token_program = item_token_program)]
+        pub seller_item_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub item_mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (init_if_needed , space = std :: mem :: size_of :: < dot :: program :: SellerProfile > () + 8 , payer = seller , seeds = ["seller" . as_bytes () . as_ref () , seller . key () . as_ref ()] , bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (init , space = std :: mem :: size_of :: < dot :: program :: EscrowOrder > () + 8 , payer = seller , seeds = ["order" . as_bytes () . as_ref () , seller . key () . as_ref () , seller_profile . next_order_id . to_le_bytes () . as_ref ()] , bump)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (init , payer = seller , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = mint , token :: authority = order)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (init , payer = seller , seeds = ["item" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump , token :: mint = item_mint , token :: authority = order , token :: token_program = item_token_program)]
+        pub item_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub rent: Sysvar<'info, Rent>,
+        pub system_program: Program<'info, System>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub item_token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn init_swap_order(
+        ctx: Context<InitSwapOrder>,
+        amount: u64,
+        item_amount: u64,
+        designated_buyer: Option<Pubkey>,
+    ) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        programs.insert(
+            "item_token_program",
+            ctx.accounts.item_token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let config = SeahorseAccount {
+            account: &ctx.accounts.config,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let seller_item_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_item_account,
+            programs: &programs_map,
+        };
+
+        let item_mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.item_mint,
+            programs: &programs_map,
+        };
+
+        let order = Empty {
+            account: dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map),
+            bump: Some(ctx.bumps.order),
+        };
+
+        let seller_profile = Empty {
+            account: dot::program::SellerProfile::load(
+                &mut ctx.accounts.seller_profile,
+                &programs_map,
+            ),
+            bump: Some(ctx.bumps.seller_profile),
+        };
+
+        let vault = Empty {
+            account: SeahorseInterfaceAccount {
+                account: &ctx.accounts.vault,
+                programs: &programs_map,
+            },
+            bump: Some(ctx.bumps.vault),
+        };
+
+        let item_vault = Empty {
+            account: SeahorseInterfaceAccount {
+                account: &ctx.accounts.item_vault,
+                programs: &programs_map,
+            },
+            bump: Some(ctx.bumps.item_vault),
+        };
+
+        init_swap_order_handler(
+            seller.clone(),
+            config.clone(),
+            seller_token_account.clone(),
+            mint.clone(),
+            seller_item_account.clone(),
+            item_mint.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            vault.clone(),
+            item_vault.clone(),
+            amount,
+            item_amount,
+            designated_buyer,
+        )?;
+
+        dot::program::EscrowOrder::store(order.account);
+
+        dot::program::SellerProfile::store(seller_profile.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    # [instruction (fee_bps : u16 , treasury : Pubkey)]
+    pub struct InitializeConfig<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        # [account (init , space = std :: mem :: size_of :: < dot :: program :: Config > () + 8 , payer = admin , seeds = ["config" . as_bytes () . as_ref ()] , bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        # [account (constraint = program . programdata_address () ? == Some (program_data . key ()))]
+        pub program: Program<'info, crate::program::SeaEscrow>,
+        # [account (constraint = program_data . upgrade_authority_address == Some (admin . key ()) @ EscrowError :: NotAdmin)]
+        pub program_data: Account<'info, ProgramData>,
+        pub system_program: Program<'info, System>,
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "system_program",
+            ctx.accounts.system_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let admin = SeahorseSigner {
+            account: &ctx.accounts.admin,
+            programs: &programs_map,
+        };
+
+        let config = Empty {
+            account: dot::program::Config::load(&mut ctx.accounts.config, &programs_map),
+            bump: Some(ctx.bumps.config),
+        };
+
+        initialize_config_handler(admin.clone(), config.clone(), fee_bps, treasury)?;
+
+        dot::program::Config::store(config.account);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct MutualCancel<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = buyer @ EscrowError :: NotBuyer , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = buyer_token_account @ EscrowError :: WrongPayoutAccount , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn mutual_cancel(ctx: Context<MutualCancel>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        mutual_cancel_handler(
+            buyer.clone(),
+            seller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            buyer_token_account.clone(),
+            seller_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ProposeAdmin<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        # [account (mut , seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , has_one = admin @ EscrowError :: NotAdmin)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let admin = SeahorseSigner {
+            account: &ctx.accounts.admin,
+            programs: &programs_map,
+        };
+
+        let config = dot::program::Config::load(&mut ctx.accounts.config, &programs_map);
+
+        propose_admin_handler(admin.clone(), config.clone(), new_admin)?;
+
+        dot::program::Config::store(config);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ReclaimContribution<'info> {
+        #[account(mut)]
+        pub contributor: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , close = contributor , seeds = ["contribution" . as_bytes () . as_ref () , order . key () . as_ref () , contributor . key () . as_ref ()] , bump = contribution . bump)]
+        pub contribution: Box<Account<'info, dot::program::Contribution>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = contributor)]
+        pub contributor_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn reclaim_contribution(ctx: Context<ReclaimContribution>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let contributor = SeahorseSigner {
+            account: &ctx.accounts.contributor,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let contribution =
+            dot::program::Contribution::load(&mut ctx.accounts.contribution, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let contributor_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.contributor_token_account,
+            programs: &programs_map,
+        };
+
+        reclaim_contribution_handler(
+            contributor.clone(),
+            order.clone(),
+            contribution.clone(),
+            mint.clone(),
+            vault.clone(),
+            contributor_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::Contribution::store(contribution);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Refund<'info> {
+        #[account(mut)]
+        pub referee: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = referee @ EscrowError :: NotReferee , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = buyer_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = referee)]
+        pub referee_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let referee = SeahorseSigner {
+            account: &ctx.accounts.referee,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let referee_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.referee_token_account,
+            programs: &programs_map,
+        };
+
+        refund_handler(
+            referee.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            buyer_token_account.clone(),
+            referee_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct RefundNative<'info> {
+        #[account(mut)]
+        pub referee: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = referee @ EscrowError :: NotReferee , has_one = buyer @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        #[account(mut)]
+        pub buyer: SystemAccount<'info>,
+    }
+
+    pub fn refund_native(ctx: Context<RefundNative>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let referee = SeahorseSigner {
+            account: &ctx.accounts.referee,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let buyer = WithPrograms {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        refund_native_handler(
+            referee.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            buyer.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Release<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn release(ctx: Context<Release>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        release_handler(
+            buyer.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ReleaseCrowdfund<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn release_crowdfund(ctx: Context<ReleaseCrowdfund>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        release_crowdfund_handler(
+            seller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ReleaseMilestone<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = buyer @ EscrowError :: NotBuyer , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn release_milestone(ctx: Context<ReleaseMilestone>, index: u8) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        release_milestone_handler(
+            buyer.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+            index,
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ReleaseNative<'info> {
+        #[account(mut)]
+        pub buyer: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = buyer @ EscrowError :: NotBuyer , has_one = seller @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        #[account(mut)]
+        pub seller: SystemAccount<'info>,
+        # [account (mut , address = config . treasury)]
+        pub treasury: SystemAccount<'info>,
+    }
+
+    pub fn release_native(ctx: Context<ReleaseNative>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let buyer = SeahorseSigner {
+            account: &ctx.accounts.buyer,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let seller = WithPrograms {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let treasury = WithPrograms {
+            account: &ctx.accounts.treasury,
+            programs: &programs_map,
+        };
+
+        release_native_handler(
             buyer.clone(),
             order.clone(),
+            seller_profile.clone(),
+            seller.clone(),
+            treasury.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Resolve<'info> {
+        #[account(mut)]
+        pub referee: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = referee @ EscrowError :: NotReferee , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = referee)]
+        pub referee_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn resolve(ctx: Context<Resolve>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let referee = SeahorseSigner {
+            account: &ctx.accounts.referee,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        let referee_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.referee_token_account,
+            programs: &programs_map,
+        };
+
+        resolve_handler(
+            referee.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+            referee_token_account.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct ResolveNative<'info> {
+        #[account(mut)]
+        pub referee: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = referee @ EscrowError :: NotReferee , has_one = seller @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        #[account(mut)]
+        pub seller: SystemAccount<'info>,
+        # [account (mut , address = config . treasury)]
+        pub treasury: SystemAccount<'info>,
+    }
+
+    pub fn resolve_native(ctx: Context<ResolveNative>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let referee = SeahorseSigner {
+            account: &ctx.accounts.referee,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let seller = WithPrograms {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let treasury = WithPrograms {
+            account: &ctx.accounts.treasury,
+            programs: &programs_map,
+        };
+
+        resolve_native_handler(
+            referee.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            seller.clone(),
+            treasury.clone(),
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct Rule<'info> {
+        #[account(mut)]
+        pub referee: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = referee @ EscrowError :: NotReferee , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = buyer_token_account @ EscrowError :: WrongPayoutAccount , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = referee)]
+        pub referee_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn rule(ctx: Context<Rule>, buyer_share: u16) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let referee = SeahorseSigner {
+            account: &ctx.accounts.referee,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        let referee_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.referee_token_account,
+            programs: &programs_map,
+        };
+
+        rule_handler(
+            referee.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
+            vault.clone(),
+            bond_vault.clone(),
+            buyer_token_account.clone(),
+            seller_token_account.clone(),
+            treasury_token_account.clone(),
+            referee_token_account.clone(),
+            buyer_share,
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SellerDispute<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+    }
+
+    pub fn seller_dispute(ctx: Context<SellerDispute>) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+
+        seller_dispute_handler(seller.clone(), order.clone(), seller_profile.clone())?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SellerRefund<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = buyer_token_account @ EscrowError :: WrongPayoutAccount , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        # [account (mut , seeds = ["seller" . as_bytes () . as_ref () , order . seller . as_ref ()] , bump = seller_profile . bump)]
+        pub seller_profile: Box<Account<'info, dot::program::SellerProfile>>,
+        # [account (seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , seeds = ["bond" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub bond_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        #[account(mut)]
+        pub buyer_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        # [account (mut , token :: mint = order . mint , token :: authority = config . treasury)]
+        pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn seller_refund(ctx: Context<SellerRefund>, amount: Option<u64>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
+        );
+
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let seller_profile =
+            dot::program::SellerProfile::load(&mut ctx.accounts.seller_profile, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let bond_vault = ctx
+            .accounts
+            .bond_vault
+            .as_ref()
+            .map(|account| SeahorseInterfaceAccount {
+                account,
+                programs: &programs_map,
+            });
+
+        let buyer_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.buyer_token_account,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        let treasury_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.treasury_token_account,
+            programs: &programs_map,
+        };
+
+        seller_refund_handler(
+            seller.clone(),
+            order.clone(),
+            seller_profile.clone(),
+            mint.clone(),
             vault.clone(),
+            bond_vault.clone(),
+            buyer_token_account.clone(),
             seller_token_account.clone(),
+            treasury_token_account.clone(),
+            amount,
+        )?;
+
+        dot::program::EscrowOrder::store(order);
+
+        dot::program::SellerProfile::store(seller_profile);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SetFee<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        # [account (mut , seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , has_one = admin @ EscrowError :: NotAdmin)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+    }
+
+    pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let admin = SeahorseSigner {
+            account: &ctx.accounts.admin,
+            programs: &programs_map,
+        };
+
+        let config = dot::program::Config::load(&mut ctx.accounts.config, &programs_map);
+
+        set_fee_handler(admin.clone(), config.clone(), fee_bps)?;
+
+        dot::program::Config::store(config);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SetPaused<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        # [account (mut , seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , has_one = admin @ EscrowError :: NotAdmin)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let admin = SeahorseSigner {
+            account: &ctx.accounts.admin,
+            programs: &programs_map,
+        };
+
+        let config = dot::program::Config::load(&mut ctx.accounts.config, &programs_map);
+
+        set_paused_handler(admin.clone(), config.clone(), paused)?;
+
+        dot::program::Config::store(config);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SetTreasury<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        # [account (mut , seeds = ["config" . as_bytes () . as_ref ()] , bump = config . bump , has_one = admin @ EscrowError :: NotAdmin)]
+        pub config: Box<Account<'info, dot::program::Config>>,
+    }
+
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+        let mut programs = HashMap::new();
+        let programs_map = ProgramsMap(programs);
+        let admin = SeahorseSigner {
+            account: &ctx.accounts.admin,
+            programs: &programs_map,
+        };
+
+        let config = dot::program::Config::load(&mut ctx.accounts.config, &programs_map);
+
+        set_treasury_handler(admin.clone(), config.clone(), treasury)?;
+
+        dot::program::Config::store(config);
+
+        return Ok(());
+    }
+
+    #[derive(Accounts)]
+    pub struct SweepExcess<'info> {
+        #[account(mut)]
+        pub seller: Signer<'info>,
+        # [account (mut , seeds = ["order" . as_bytes () . as_ref () , order . seller . as_ref () , order . order_id . to_le_bytes () . as_ref ()] , bump = order . bump , has_one = seller @ EscrowError :: NotSeller , has_one = mint @ EscrowError :: WrongMint , has_one = vault @ EscrowError :: WrongVault , has_one = seller_token_account @ EscrowError :: WrongPayoutAccount)]
+        pub order: Box<Account<'info, dot::program::EscrowOrder>>,
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        # [account (mut , seeds = ["vault" . as_bytes () . as_ref () , order . key () . as_ref ()] , bump)]
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub seller_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn sweep_excess(ctx: Context<SweepExcess>) -> Result<()> {
+        let mut programs = HashMap::new();
+
+        programs.insert(
+            "token_program",
+            ctx.accounts.token_program.to_account_info(),
         );
 
+        let programs_map = ProgramsMap(programs);
+        let seller = SeahorseSigner {
+            account: &ctx.accounts.seller,
+            programs: &programs_map,
+        };
+
+        let order = dot::program::EscrowOrder::load(&mut ctx.accounts.order, &programs_map);
+        let mint = SeahorseInterfaceAccount {
+            account: &ctx.accounts.mint,
+            programs: &programs_map,
+        };
+
+        let vault = SeahorseInterfaceAccount {
+            account: &ctx.accounts.vault,
+            programs: &programs_map,
+        };
+
+        let seller_token_account = SeahorseInterfaceAccount {
+            account: &ctx.accounts.seller_token_account,
+            programs: &programs_map,
+        };
+
+        sweep_excess_handler(
+            seller.clone(),
+            order.clone(),
+            mint.clone(),
+            vault.clone(),
+            seller_token_account.clone(),
+        )?;
+
         dot::program::EscrowOrder::store(order);
 
         return Ok(());
@@ -1,15 +1,91 @@
-import { getAccount } from "@solana/spl-token"
+import {
+    ExtensionType,
+    TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+    createAccount,
+    createInitializeMintInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    createMint,
+    getAccount,
+    getMintLen,
+    getTransferFeeAmount,
+    mintTo,
+    transfer,
+} from "@solana/spl-token"
 import * as anchor from "@coral-xyz/anchor";
 import { web3, Program } from "@coral-xyz/anchor";
 import BN from "bn.js";
 import { SeaEscrow } from "../target/types/sea_escrow";
-import { SimpleUser, findProgramAddress, u16 } from "@solardev/simple-web3";
+import { MockPyth } from "../target/types/mock_pyth";
+import { SimpleUser, findProgramAddress } from "@solardev/simple-web3";
 const assert = require("assert");
 
 const provider = anchor.AnchorProvider.env();
 anchor.setProvider(provider);
 const program = anchor.workspace.SeaEscrow as Program<SeaEscrow>;
 
+// loaded at the Pyth devnet oracle address, see programs/mock_pyth
+const pyth = anchor.workspace.MockPyth as Program<MockPyth>;
+
+function u64(value: number) {
+    return new BN(value).toArrayLike(Buffer, "le", 8);
+}
+
+const RELEASE_WINDOW = new BN(7 * 24 * 60 * 60);
+const DISPUTE_WINDOW = new BN(14 * 24 * 60 * 60);
+
+async function fetchEvents(signature: string) {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, { commitment: "confirmed" });
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    return [...parser.parseLogs(tx.meta.logMessages)];
+}
+
+// unix timestamp of the validator clock, which is what deadlines are
+// checked against
+async function clockTime() {
+    const { data } = await provider.connection.getAccountInfo(web3.SYSVAR_CLOCK_PUBKEY);
+    return Number(data.readBigInt64LE(32));
+}
+
+// waits on the validator clock rather than wall-clock time, so deadline
+// tests hold however fast the validator produces slots
+async function waitForClock(timestamp: number) {
+    while (await clockTime() < timestamp) {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+}
+
+const [configAddress, ] = findProgramAddress(program.programId, ["config"]);
+
+// owner of the protocol fee token accounts
+let treasury: SimpleUser;
+
+// the config is a singleton, so it is created once for every route by the
+// upgrade authority, which is the provider wallet on localnet
+before("initialize config", async () => {
+    treasury = await SimpleUser.generate(provider.connection);
+
+    const config = await program.account.config.fetchNullable(configAddress);
+    if (config != null) {
+        return;
+    }
+
+    const [programData, ] = web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    await program.methods.initializeConfig(0, treasury.publicKey)
+        .accounts({
+            admin: provider.wallet.publicKey,
+            config: configAddress,
+            program: program.programId,
+            programData,
+        })
+        .rpc({ skipPreflight: true });
+});
+
 describe("Seahorse Escrow - Settle Route", () => {
 
     let minter: SimpleUser;
@@ -19,6 +95,7 @@ describe("Seahorse Escrow - Settle Route", () => {
 
     let orderId: number;
     let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
     let vaultAddress: web3.PublicKey;
 
     before("", async () => {
@@ -31,13 +108,25 @@ describe("Seahorse Escrow - Settle Route", () => {
         await minter.mint("USDC")
             .transfer("USDC", 200, buyer)
             .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .transfer("USDC", 0, referee)
+            .commit();
+
+        await minter.mint("USDT")
+            .transfer("USDT", 0, seller)
+            .transfer("USDT", 200, buyer)
             .commit();
 
-        orderId = 255;
+        orderId = 0;
 
         [orderAddress, ] = findProgramAddress(
             program.programId,
-            ["order", seller.publicKey, u16(orderId)]
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
         );
 
         [vaultAddress,] = findProgramAddress(
@@ -46,17 +135,101 @@ describe("Seahorse Escrow - Settle Route", () => {
         );
     });
 
+    it("seller cannot set a commission above 100%", async () => {
+        let success = false;
+        let code: string;
+        const amount = new BN(100 * Math.pow(10, 9));
+
+        try {
+            await program.methods.initOrder(referee.publicKey, amount, 10001, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "CommissionTooHigh");
+    });
+
+    it("seller token account must hold the order mint", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: seller.tokenAccounts["USDT"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenMint");
+    });
+
+    it("seller token account must belong to the seller", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: buyer.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenOwner");
+    });
+
     it("seller can initiate an escrow order", async () => {
 
         const amount = new BN(100 * Math.pow(10, 9));
         
-        await program.methods.initOrder(orderId, referee.publicKey, amount)
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
             .accounts({
                 seller: seller.publicKey,
                 sellerTokenAccount: seller.tokenAccounts["USDC"],
                 mint: minter.tokens["USDC"].mint,
                 order: orderAddress,
-                vault: vaultAddress
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
             })
             .signers([seller])
             .rpc({ skipPreflight: true });
@@ -66,6 +239,11 @@ describe("Seahorse Escrow - Settle Route", () => {
         assert.ok(order.sellerTokenAccount.toBase58() == seller.tokenAccounts["USDC"].toBase58());
         assert.ok(order.mint.toBase58() == minter.tokens["USDC"].mint.toBase58());
         assert.ok(order.amount.toNumber() == 100 * Math.pow(10, 9));
+        assert.ok(order.commission == 0);
+        assert.ok(order.releaseWindow.eq(RELEASE_WINDOW));
+        assert.ok(order.disputeWindow.eq(DISPUTE_WINDOW));
+        assert.ok(order.disputeFallback.refund);
+        assert.ok(order.designatedBuyer == null);
         assert.ok(order.vault.toBase58() == vaultAddress.toBase58());
         assert.ok(order.state.pending);
 
@@ -75,30 +253,114 @@ describe("Seahorse Escrow - Settle Route", () => {
 
     it("buyer cannot release vault funds before deposit", async () => {
         let success = false;
+        let code: string;
         
         try {
             await program.methods.release()
                 .accounts({
                     buyer: buyer.publicKey,
                     order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
                     vault: vaultAddress,
+                    bondVault: null,
                     sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
                 })
                 .signers([buyer])
                 .rpc({ skipPreflight: true });
             
             success = true;
-        } catch(err) {}
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
         
-        assert.ok(success == false)
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("buyer must deposit tokens of the order mint", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: buyer.tokenAccounts["USDT"],
+                    vault: vaultAddress
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenMint");
+    });
+
+    it("buyer must deposit from his own token account", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: seller.tokenAccounts["USDC"],
+                    vault: vaultAddress
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenOwner");
+    });
+
+    it("buyer must deposit to the order vault", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: seller.tokenAccounts["USDC"]
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongVault");
     });
 
     it("buyer can deposit to the order vault", async () => {
 
-        await program.methods.deposit()
+        const tx = await program.methods.deposit()
             .accounts({
                 buyer: buyer.publicKey,
                 order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
                 buyerTokenAccount: buyer.tokenAccounts["USDC"],
                 vault: vaultAddress,
             })
@@ -115,17 +377,29 @@ describe("Seahorse Escrow - Settle Route", () => {
         assert.ok(order.buyer.toBase58() == buyer.publicKey.toBase58());
         assert.ok(order.buyerTokenAccount.toBase58() == buyer.tokenAccounts["USDC"].toBase58());
         assert.ok(order.state.deposited);
+        assert.ok(order.releaseDeadline.toNumber() > 0);
+
+        const [event] = await fetchEvents(tx);
+        assert.ok(event.name == "OrderStateChanged");
+        assert.ok(event.data.order.toBase58() == orderAddress.toBase58());
+        assert.ok(event.data.actor.toBase58() == buyer.publicKey.toBase58());
+        assert.ok(event.data.mint.toBase58() == minter.tokens["USDC"].mint.toBase58());
+        assert.ok(event.data.amount.toNumber() == 100 * Math.pow(10, 9));
+        assert.ok(event.data.oldState.pending);
+        assert.ok(event.data.newState.deposited);
     });
 
     it("buyer cannot deposit repeatedly", async () => {
 
         let success = false;
+        let code: string;
 
         try {
             await program.methods.deposit()
                 .accounts({
                     buyer: buyer.publicKey,
                     order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
                     buyerTokenAccount: buyer.tokenAccounts["USDC"],
                     vault: vaultAddress,
                 })
@@ -133,33 +407,100 @@ describe("Seahorse Escrow - Settle Route", () => {
                 .rpc({ skipPreflight: true });
             
             success = true;
-        } catch(err) {}
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("seller cannot cancel after deposit", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.cancelOrder()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
 
-        assert.ok(success == false)
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
     });
 
     it("buyer must release to the seller token account", async () => {
         let success = false;
+        let code: string;
         
         try {
             await program.methods.release()
                 .accounts({
                     buyer: buyer.publicKey,
                     order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
                     vault: vaultAddress,
+                    bondVault: null,
                     sellerTokenAccount: buyer.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPayoutAccount");
+    });
+
+    it("buyer must release with the order mint", async () => {
+        let success = false;
+        let code: string;
+        
+        try {
+            await program.methods.release()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDT"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
                 })
                 .signers([buyer])
                 .rpc({ skipPreflight: true });
             
             success = true;
-        } catch(err) {}
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
 
-        assert.ok(success == false)
+        assert.ok(success == false);
+        assert.ok(code == "WrongMint");
     });
 
     it("only the buyer can release vault funds", async () => {
         let success = false;
+        let code: string;
         const hacker = await SimpleUser.generate(provider.connection);
         
         try {
@@ -167,16 +508,40 @@ describe("Seahorse Escrow - Settle Route", () => {
                 .accounts({
                     buyer: hacker.publicKey,
                     order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
                     vault: vaultAddress,
+                    bondVault: null,
                     sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
                 })
                 .signers([hacker])
                 .rpc({ skipPreflight: true });
             
             success = true;
-        } catch(err) {}
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
 
         assert.ok(success == false);
+        assert.ok(code == "NotBuyer");
+    });
+
+    it("tokens sent straight to the vault are not part of the deposit", async () => {
+        await transfer(
+            provider.connection,
+            buyer,
+            buyer.tokenAccounts["USDC"],
+            vaultAddress,
+            buyer,
+            10 * Math.pow(10, 9)
+        );
+
+        const vault = await getAccount(provider.connection, vaultAddress);
+        assert.ok(Number(vault.amount) == 110 * Math.pow(10, 9));
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.depositedAmount.toNumber() == 100 * Math.pow(10, 9));
     });
 
     it("buyer can release vault funds", async () => {
@@ -185,8 +550,12 @@ describe("Seahorse Escrow - Settle Route", () => {
             .accounts({
                 buyer: buyer.publicKey,
                 order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
                 vault: vaultAddress,
+                bondVault: null,
                 sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
             })
             .signers([buyer])
             .rpc({ skipPreflight: true });
@@ -196,25 +565,110 @@ describe("Seahorse Escrow - Settle Route", () => {
 
         const order = await program.account.escrowOrder.fetch(orderAddress);
         assert.ok(order.state.settled);
+
+        const vault = await getAccount(provider.connection, vaultAddress);
+        assert.ok(Number(vault.amount) == 10 * Math.pow(10, 9));
+    });
+
+    it("seller can sweep excess tokens from the vault", async () => {
+        await program.methods.sweepExcess()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 110);
+
+        const vault = await getAccount(provider.connection, vaultAddress);
+        assert.ok(Number(vault.amount) == 0);
     });
 
     it("buyer cannot dispute after settled", async () => {
         let success = false;
+        let code: string;
 
         try {
             await program.methods.dispute()
                 .accounts({
                     buyer: buyer.publicKey,
-                    order: orderAddress
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
                 })
                 .signers([buyer])
                 .rpc({ skipPreflight: true });
             
             success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
         }
-        catch (err) {}
 
         assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("seller can close a settled order and reclaim rent", async () => {
+        await program.methods.closeOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+
+        const vault = await provider.connection.getAccountInfo(vaultAddress);
+        assert.ok(vault == null);
+    });
+
+    it("closed orders do not hold back the next order id", async () => {
+        const amount = new BN(50 * Math.pow(10, 9));
+
+        orderId = 1;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.orderId.toNumber() == 1);
+        assert.ok(order.amount.toNumber() == 50 * Math.pow(10, 9));
+        assert.ok(order.state.pending);
+
+        const profile = await program.account.sellerProfile.fetch(profileAddress);
+        assert.ok(profile.nextOrderId.toNumber() == 2);
+        assert.ok(profile.openOrders.toNumber() == 1);
+        assert.ok(profile.settledOrders.toNumber() == 1);
     });
 });
 
@@ -227,6 +681,7 @@ describe("Seahorse Escrow - Dispute Route", async () => {
 
     let orderId: number;
     let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
     let vaultAddress: web3.PublicKey;
 
     before("", async () => {
@@ -239,13 +694,20 @@ describe("Seahorse Escrow - Dispute Route", async () => {
         await minter.mint("USDC")
             .transfer("USDC", 200, buyer)
             .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .transfer("USDC", 0, referee)
             .commit();
 
-        orderId = 256;
+        orderId = 0;
 
         [orderAddress, ] = findProgramAddress(
             program.programId,
-            ["order", seller.publicKey, u16(orderId)]
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
         );
 
         [vaultAddress,] = findProgramAddress(
@@ -254,13 +716,15 @@ describe("Seahorse Escrow - Dispute Route", async () => {
         );
 
         const amount = new BN(100 * Math.pow(10, 9));
-        await program.methods.initOrder(orderId, referee.publicKey, amount)
+        await program.methods.initOrder(referee.publicKey, amount, 500, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
             .accounts({
                 seller: seller.publicKey,
                 sellerTokenAccount: seller.tokenAccounts["USDC"],
                 mint: minter.tokens["USDC"].mint,
                 order: orderAddress,
-                vault: vaultAddress
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
             })
             .signers([seller])
             .rpc({ skipPreflight: true });
@@ -269,6 +733,7 @@ describe("Seahorse Escrow - Dispute Route", async () => {
             .accounts({
                 buyer: buyer.publicKey,
                 order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
                 buyerTokenAccount: buyer.tokenAccounts["USDC"],
                 vault: vaultAddress,
             })
@@ -278,52 +743,4239 @@ describe("Seahorse Escrow - Dispute Route", async () => {
 
     it("only the buyer can dispute his escort order", async () => {
         let success = false;
+        let code: string;
         const hacker = await SimpleUser.generate(provider.connection);
 
         try {
             await program.methods.dispute()
                 .accounts({
                     buyer: hacker.publicKey,
-                    order: orderAddress
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
                 })
                 .signers([hacker])
                 .rpc({ skipPreflight: true });
             
             success = true;
-        } catch(err) {}
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
         
         assert.ok(success == false);
+        assert.ok(code == "NotBuyer");
     });
 
     it("buyer can dispute his escort order", async () => {
-        await program.methods.dispute()
+        const tx = await program.methods.dispute()
             .accounts({
                 buyer: buyer.publicKey,
-                order: orderAddress
+                order: orderAddress,
+                sellerProfile: profileAddress,
             })
             .signers([buyer])
             .rpc({ skipPreflight: true });
             
         const order = await program.account.escrowOrder.fetch(orderAddress);
         assert.ok(order.state.dispute);
+        assert.ok(order.disputer.toBase58() == buyer.publicKey.toBase58());
+        assert.ok(order.disputeDeadline.toNumber() > 0);
+
+        const profile = await program.account.sellerProfile.fetch(profileAddress);
+        assert.ok(profile.disputedOrders.toNumber() == 1);
+
+        const [event] = await fetchEvents(tx);
+        assert.ok(event.name == "OrderStateChanged");
+        assert.ok(event.data.actor.toBase58() == buyer.publicKey.toBase58());
+        assert.ok(event.data.oldState.deposited);
+        assert.ok(event.data.newState.dispute);
+        assert.ok(event.data.timestamp.toNumber() > 0);
     });
 
-    it("referee can refund to the buyer", async() => {
-        await program.methods.refund()
-            .accounts({
-                referee: referee.publicKey,
-                order: orderAddress,
-                vault: vaultAddress,
-                buyerTokenAccount: buyer.tokenAccounts["USDC"]
-            })
-            .signers([referee])
-            .rpc({ skipPreflight: true });
+    it("dispute stops the release timer", async() => {
+        let success = false;
+        let code: string;
 
-        const order = await program.account.escrowOrder.fetch(orderAddress);
-        assert.ok(order.state.refunded);
-        
-        const { amount } = await buyer.balance("USDC");
-        assert.ok(amount == 200);
+        try {
+            await program.methods.claimAfterTimeout()
+                .accounts({
+                    caller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("seller cannot sweep the deposit of an open order", async() => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sweepExcess()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NothingToSweep");
+    });
+
+    it("seller cannot close an order under dispute", async() => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.closeOrder()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("referee commission must go to the referee token account", async() => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.refund()
+                .accounts({
+                    referee: referee.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    refereeTokenAccount: seller.tokenAccounts["USDC"]
+                })
+                .signers([referee])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenOwner");
+    });
+
+    it("referee can refund to the buyer less commission", async() => {
+        await program.methods.refund()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                refereeTokenAccount: referee.tokenAccounts["USDC"]
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.refunded);
+        
+        const { amount } = await buyer.balance("USDC");
+        assert.ok(amount == 195);
+
+        const { amount: commission } = await referee.balance("USDC");
+        assert.ok(commission == 5);
     })
 
 });
+
+describe("Seahorse Escrow - Resolve Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, referee)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 100, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("referee cannot resolve before dispute", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.resolve()
+                .accounts({
+                    referee: referee.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                    refereeTokenAccount: referee.tokenAccounts["USDC"]
+                })
+                .signers([referee])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("only the assigned referee can resolve", async () => {
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+        const hacker = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.resolve()
+                .accounts({
+                    referee: hacker.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                    refereeTokenAccount: referee.tokenAccounts["USDC"]
+                })
+                .signers([hacker])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotReferee");
+    });
+
+    it("resolving must release to the seller token account", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.resolve()
+                .accounts({
+                    referee: referee.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: buyer.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                    refereeTokenAccount: referee.tokenAccounts["USDC"]
+                })
+                .signers([referee])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPayoutAccount");
+    });
+
+    it("referee can resolve to the seller less commission", async () => {
+        await program.methods.resolve()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                refereeTokenAccount: referee.tokenAccounts["USDC"]
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.resolved);
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 99);
+
+        const { amount: commission } = await referee.balance("USDC");
+        assert.ok(commission == 1);
+    });
+
+});
+
+describe("Seahorse Escrow - Seller Dispute Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, referee)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("seller cannot dispute before deposit", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sellerDispute()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+        
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("only the seller can raise a seller dispute", async () => {
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+        const hacker = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.sellerDispute()
+                .accounts({
+                    seller: hacker.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                })
+                .signers([hacker])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+        
+        assert.ok(success == false);
+        assert.ok(code == "NotSeller");
+    });
+
+    it("seller can dispute his escrow order", async () => {
+        await program.methods.sellerDispute()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+            
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.dispute);
+        assert.ok(order.disputer.toBase58() == seller.publicKey.toBase58());
+    });
+
+    it("referee can resolve a seller dispute", async () => {
+        await program.methods.resolve()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                refereeTokenAccount: referee.tokenAccounts["USDC"]
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.resolved);
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 100);
+    });
+
+});
+
+describe("Seahorse Escrow - Timeout Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, new BN(10), DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("nobody can claim before the release deadline", async () => {
+        let success = false;
+        let code: string;
+        const stranger = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.claimAfterTimeout()
+                .accounts({
+                    caller: stranger.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([stranger])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ReleaseDeadlineNotPassed");
+    });
+
+    it("anyone can release to the seller after the release deadline", async () => {
+        const { releaseDeadline } = await program.account.escrowOrder.fetch(orderAddress);
+        await waitForClock(releaseDeadline.toNumber());
+        const stranger = await SimpleUser.generate(provider.connection);
+
+        await program.methods.claimAfterTimeout()
+            .accounts({
+                caller: stranger.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([stranger])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.settled);
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 100);
+    });
+
+});
+
+describe("Seahorse Escrow - Dispute Fallback Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, new BN(10), { split: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("nobody can apply the fallback before the dispute deadline", async () => {
+        let success = false;
+        let code: string;
+        const stranger = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.claimAfterDisputeTimeout()
+                .accounts({
+                    caller: stranger.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([stranger])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "DisputeDeadlineNotPassed");
+    });
+
+    it("anyone can split the vault after the dispute deadline", async () => {
+        const { disputeDeadline } = await program.account.escrowOrder.fetch(orderAddress);
+        await waitForClock(disputeDeadline.toNumber());
+        const stranger = await SimpleUser.generate(provider.connection);
+
+        await program.methods.claimAfterDisputeTimeout()
+            .accounts({
+                caller: stranger.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([stranger])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.resolved);
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 150);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 50);
+    });
+
+});
+
+describe("Seahorse Escrow - Cancel Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 0, seller)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("only the seller can cancel his order", async () => {
+        let success = false;
+        let code: string;
+        const hacker = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.cancelOrder()
+                .accounts({
+                    seller: hacker.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([hacker])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotSeller");
+    });
+
+    it("seller can cancel a pending order and reclaim rent", async () => {
+        const before = await provider.connection.getBalance(seller.publicKey);
+
+        const tx = await program.methods.cancelOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const [event] = await fetchEvents(tx);
+        assert.ok(event.data.newState.cancelled);
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+
+        const vault = await provider.connection.getAccountInfo(vaultAddress);
+        assert.ok(vault == null);
+
+        const after = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(after > before);
+    });
+
+});
+
+describe("Seahorse Escrow - Designated Buyer Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+    let stranger: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+        stranger = await SimpleUser.generate(provider.connection);
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 200, stranger)
+            .transfer("USDC", 0, seller)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, buyer.publicKey, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("only the designated buyer can deposit", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: stranger.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: stranger.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([stranger])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotDesignatedBuyer");
+    });
+
+    it("designated buyer can deposit", async () => {
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.buyer.toBase58() == buyer.publicKey.toBase58());
+        assert.ok(order.state.deposited);
+    });
+
+});
+
+describe("Seahorse Escrow - Token-2022 Route", async () => {
+
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let mint: web3.Keypair;
+    let sellerTokenAccount: web3.PublicKey;
+    let buyerTokenAccount: web3.PublicKey;
+    let treasuryTokenAccount: web3.PublicKey;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        // a 1% transfer fee mint, so every transfer lands 99% of its amount
+        mint = web3.Keypair.generate();
+        const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+        const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+        await web3.sendAndConfirmTransaction(
+            provider.connection,
+            new web3.Transaction().add(
+                web3.SystemProgram.createAccount({
+                    fromPubkey: seller.publicKey,
+                    newAccountPubkey: mint.publicKey,
+                    space: mintLen,
+                    lamports,
+                    programId: TOKEN_2022_PROGRAM_ID,
+                }),
+                createInitializeTransferFeeConfigInstruction(
+                    mint.publicKey, seller.publicKey, seller.publicKey, 100, BigInt(1000 * Math.pow(10, 9)), TOKEN_2022_PROGRAM_ID
+                ),
+                createInitializeMintInstruction(mint.publicKey, 9, seller.publicKey, null, TOKEN_2022_PROGRAM_ID)
+            ),
+            [seller, mint]
+        );
+
+        sellerTokenAccount = await createAccount(
+            provider.connection, seller, mint.publicKey, seller.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+        buyerTokenAccount = await createAccount(
+            provider.connection, buyer, mint.publicKey, buyer.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+        treasuryTokenAccount = await createAccount(
+            provider.connection, seller, mint.publicKey, treasury.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+
+        await mintTo(
+            provider.connection, seller, mint.publicKey, buyerTokenAccount, seller, 200 * Math.pow(10, 9), [], undefined, TOKEN_2022_PROGRAM_ID
+        );
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount,
+                mint: mint.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("deposit records what the vault received after the transfer fee", async () => {
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: mint.publicKey,
+                buyerTokenAccount,
+                vault: vaultAddress,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const vault = await getAccount(provider.connection, vaultAddress, undefined, TOKEN_2022_PROGRAM_ID);
+        assert.ok(Number(vault.amount) == 99 * Math.pow(10, 9));
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.depositedAmount.toNumber() == 99 * Math.pow(10, 9));
+    });
+
+    it("buyer can release the received amount to the seller", async () => {
+        await program.methods.release()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: mint.publicKey,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount,
+                treasuryTokenAccount,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const vault = await getAccount(provider.connection, vaultAddress, undefined, TOKEN_2022_PROGRAM_ID);
+        assert.ok(Number(vault.amount) == 0);
+
+        const sellerAccount = await getAccount(provider.connection, sellerTokenAccount, undefined, TOKEN_2022_PROGRAM_ID);
+        assert.ok(Number(sellerAccount.amount) == 9801 * Math.pow(10, 7));
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.settled);
+    });
+
+    it("seller can close the order although the vault withheld transfer fees", async () => {
+        let vault = await getAccount(provider.connection, vaultAddress, undefined, TOKEN_2022_PROGRAM_ID);
+        assert.ok(Number(getTransferFeeAmount(vault).withheldAmount) > 0);
+
+        await program.methods.closeOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                mint: mint.publicKey,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const closed = await provider.connection.getAccountInfo(vaultAddress);
+        assert.ok(closed == null);
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+    });
+
+});
+
+describe("Seahorse Escrow - Native Route", async () => {
+
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+
+    const amount = new BN(web3.LAMPORTS_PER_SOL / 2);
+
+    before("", async () => {
+
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        await program.methods.initNativeOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("buyer can deposit lamports to the order", async () => {
+        const before = await provider.connection.getBalance(orderAddress);
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const after = await provider.connection.getBalance(orderAddress);
+        assert.ok(after - before == amount.toNumber());
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.mode.native);
+        assert.ok(order.state.deposited);
+    });
+
+    it("buyer must release to the seller", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.releaseNative()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    seller: referee.publicKey,
+                    treasury: treasury.publicKey,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPayoutAccount");
+    });
+
+    it("buyer can release lamports to the seller", async () => {
+        const before = await provider.connection.getBalance(seller.publicKey);
+
+        await program.methods.releaseNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                seller: seller.publicKey,
+                treasury: treasury.publicKey,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const after = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(after - before == amount.toNumber());
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.settled);
+    });
+
+    it("seller can close a settled native order", async () => {
+        await program.methods.closeNativeOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+    });
+
+
+    it("referee refund pays the commission and returns the rest to the buyer", async () => {
+        orderId = 1;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        await program.methods.initNativeOrder(referee.publicKey, amount, 500, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const buyerBefore = await provider.connection.getBalance(buyer.publicKey);
+        const refereeBefore = await provider.connection.getBalance(referee.publicKey);
+
+        await program.methods.refundNative()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                buyer: buyer.publicKey,
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        // a 5% commission
+        const fee = amount.toNumber() * 500 / 10000;
+
+        const buyerAfter = await provider.connection.getBalance(buyer.publicKey);
+        assert.ok(buyerAfter - buyerBefore == amount.toNumber() - fee);
+
+        const refereeAfter = await provider.connection.getBalance(referee.publicKey);
+        assert.ok(refereeAfter - refereeBefore == fee);
+
+        // the order keeps its rent after paying out the deposit
+        const orderInfo = await provider.connection.getAccountInfo(orderAddress);
+        const rent = await provider.connection.getMinimumBalanceForRentExemption(orderInfo.data.length);
+        assert.ok(orderInfo.lamports >= rent);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.refunded);
+    });
+
+    it("referee resolve pays the commission and releases the rest to the seller", async () => {
+        orderId = 2;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        await program.methods.initNativeOrder(referee.publicKey, amount, 500, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+        const refereeBefore = await provider.connection.getBalance(referee.publicKey);
+
+        await program.methods.resolveNative()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                seller: seller.publicKey,
+                treasury: treasury.publicKey,
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        // a 5% commission
+        const fee = amount.toNumber() * 500 / 10000;
+
+        const sellerAfter = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(sellerAfter - sellerBefore == amount.toNumber() - fee);
+
+        const refereeAfter = await provider.connection.getBalance(referee.publicKey);
+        assert.ok(refereeAfter - refereeBefore == fee);
+
+        // the order keeps its rent after paying out the deposit
+        const orderInfo = await provider.connection.getAccountInfo(orderAddress);
+        const rent = await provider.connection.getMinimumBalanceForRentExemption(orderInfo.data.length);
+        assert.ok(orderInfo.lamports >= rent);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.resolved);
+    });
+
+});
+
+describe("Seahorse Escrow - Native Timeout Route", async () => {
+
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let profileAddress: web3.PublicKey;
+    let timeoutAddress: web3.PublicKey;
+    let fallbackAddress: web3.PublicKey;
+
+    const amount = new BN(web3.LAMPORTS_PER_SOL / 2);
+
+    before("", async () => {
+
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [timeoutAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(0)]
+        );
+
+        [fallbackAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(1)]
+        );
+    });
+
+    it("seller must set a release window", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.initNativeOrder(referee.publicKey, amount, 0, new BN(0), DISPUTE_WINDOW, { refund: {} }, null)
+                .accounts({
+                    seller: seller.publicKey,
+                    order: timeoutAddress,
+                    sellerProfile: profileAddress,
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidReleaseWindow");
+    });
+
+    it("nobody can claim lamports before the release deadline", async () => {
+        await program.methods.initNativeOrder(referee.publicKey, amount, 0, new BN(10), DISPUTE_WINDOW, { refund: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: timeoutAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: timeoutAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+        const stranger = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.claimAfterTimeoutNative()
+                .accounts({
+                    caller: stranger.publicKey,
+                    order: timeoutAddress,
+                    sellerProfile: profileAddress,
+                    seller: seller.publicKey,
+                    treasury: treasury.publicKey,
+                })
+                .signers([stranger])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ReleaseDeadlineNotPassed");
+    });
+
+    it("anyone can release lamports to the seller after the release deadline", async () => {
+        const { releaseDeadline } = await program.account.escrowOrder.fetch(timeoutAddress);
+        await waitForClock(releaseDeadline.toNumber());
+        const stranger = await SimpleUser.generate(provider.connection);
+        const before = await provider.connection.getBalance(seller.publicKey);
+
+        await program.methods.claimAfterTimeoutNative()
+            .accounts({
+                caller: stranger.publicKey,
+                order: timeoutAddress,
+                sellerProfile: profileAddress,
+                seller: seller.publicKey,
+                treasury: treasury.publicKey,
+            })
+            .signers([stranger])
+            .rpc({ skipPreflight: true });
+
+        const after = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(after - before == amount.toNumber());
+
+        const order = await program.account.escrowOrder.fetch(timeoutAddress);
+        assert.ok(order.state.settled);
+    });
+
+    it("anyone can split the lamports after the dispute deadline", async () => {
+        await program.methods.initNativeOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, new BN(10), { split: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: fallbackAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: fallbackAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: fallbackAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const { disputeDeadline } = await program.account.escrowOrder.fetch(fallbackAddress);
+        await waitForClock(disputeDeadline.toNumber());
+        const stranger = await SimpleUser.generate(provider.connection);
+        const buyerBefore = await provider.connection.getBalance(buyer.publicKey);
+        const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+
+        await program.methods.claimAfterDisputeTimeoutNative()
+            .accounts({
+                caller: stranger.publicKey,
+                order: fallbackAddress,
+                sellerProfile: profileAddress,
+                buyer: buyer.publicKey,
+                seller: seller.publicKey,
+                treasury: treasury.publicKey,
+            })
+            .signers([stranger])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(fallbackAddress);
+        assert.ok(order.state.resolved);
+
+        const buyerAfter = await provider.connection.getBalance(buyer.publicKey);
+        assert.ok(buyerAfter - buyerBefore == amount.toNumber() / 2);
+
+        const sellerAfter = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(sellerAfter - sellerBefore == amount.toNumber() / 2);
+    });
+
+});
+
+describe("Seahorse Escrow - USD Route", async () => {
+
+    // tests/fixtures/usd_price_feed.json, a Pyth price account quoting 1.00 USD
+    // with a publish time in 2023, loaded by the test validator and owned by
+    // the mock oracle, which publishes fresh prices to it
+    const PRICE_FEED = new web3.PublicKey("1ZnaUwmAWTYAkpjsqXuCY7xUCoZETLun6GztTTnHFrE");
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+    });
+
+    it("seller can price an order in USD cents", async () => {
+        const usdPrice = { cents: new BN(2500), priceFeed: PRICE_FEED };
+        await program.methods.initOrder(referee.publicKey, new BN(0), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, usdPrice, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.usdPrice.cents.toNumber() == 2500);
+        assert.ok(order.usdPrice.priceFeed.toBase58() == PRICE_FEED.toBase58());
+    });
+
+    it("buyer cannot deposit a fixed amount to a USD order", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "PricedInUsd");
+    });
+
+    it("deposit must read the price feed of the order", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.depositUsd(new BN(200 * Math.pow(10, 9)))
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    priceFeed: referee.publicKey,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPriceFeed");
+    });
+
+    it("deposit rejects a stale price", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.depositUsd(new BN(200 * Math.pow(10, 9)))
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    priceFeed: PRICE_FEED,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "StalePrice");
+    });
+
+    it("deposit rejects a price feed the Pyth oracle does not own", async () => {
+        const [otherAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(1)]
+        );
+
+        const [otherVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", otherAddress]
+        );
+
+        const usdPrice = { cents: new BN(2500), priceFeed: referee.publicKey };
+        await program.methods.initOrder(referee.publicKey, new BN(0), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, usdPrice, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: otherAddress,
+                sellerProfile: profileAddress,
+                vault: otherVaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.depositUsd(new BN(200 * Math.pow(10, 9)))
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: otherAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    priceFeed: referee.publicKey,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: otherVaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "PriceFeedNotPyth");
+    });
+
+    it("deposit rejects a price with a wide confidence interval", async () => {
+        // 0.50 USD with a 3% confidence interval, above the 2% limit
+        await pyth.methods.setPrice(new BN(50000000), new BN(1500000))
+            .accounts({ priceFeed: PRICE_FEED })
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.depositUsd(new BN(200 * Math.pow(10, 9)))
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    priceFeed: PRICE_FEED,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "PriceTooUncertain");
+    });
+
+    it("deposit rejects an amount above the buyer's maximum", async () => {
+        // 0.50 USD with a 0.05% confidence interval
+        await pyth.methods.setPrice(new BN(50000000), new BN(25000))
+            .accounts({ priceFeed: PRICE_FEED })
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.depositUsd(new BN(49 * Math.pow(10, 9)))
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    priceFeed: PRICE_FEED,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "SlippageExceeded");
+    });
+
+    it("buyer can deposit the USD price converted at the feed price", async () => {
+        await pyth.methods.setPrice(new BN(50000000), new BN(25000))
+            .accounts({ priceFeed: PRICE_FEED })
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositUsd(new BN(50 * Math.pow(10, 9)))
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                priceFeed: PRICE_FEED,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        // 25.00 USD at 0.50 USD per token
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.deposited);
+        assert.ok(order.amount.toNumber() == 50 * Math.pow(10, 9));
+
+        const { amount } = await buyer.balance("USDC");
+        assert.ok(amount == 150);
+    });
+
+});
+
+describe("Seahorse Escrow - Milestone Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .transfer("USDC", 0, referee)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+    });
+
+    it("milestones must add up to the order amount", async () => {
+        let success = false;
+        let code: string;
+        const milestones = [new BN(30 * Math.pow(10, 9)), new BN(60 * Math.pow(10, 9))];
+
+        try {
+            await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, milestones, new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidMilestones");
+    });
+
+    it("milestones cannot be combined with a USD price", async () => {
+        let success = false;
+        let code: string;
+        const milestones = [new BN(30 * Math.pow(10, 9)), new BN(70 * Math.pow(10, 9))];
+        const usdPrice = { cents: new BN(2500), priceFeed: referee.publicKey };
+
+        try {
+            await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, usdPrice, milestones, new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidMilestones");
+    });
+
+    it("seller can split an order into milestones", async () => {
+        const milestones = [new BN(30 * Math.pow(10, 9)), new BN(70 * Math.pow(10, 9))];
+        await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, milestones, new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.milestoneCount == 2);
+    });
+
+    it("buyer can release one milestone at a time", async () => {
+        const tx = await program.methods.releaseMilestone(0)
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 30);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.deposited);
+
+        const [event] = await fetchEvents(tx);
+        assert.ok(event.name == "OrderStateChanged");
+        assert.ok(event.data.amount.toNumber() == 30 * Math.pow(10, 9));
+        assert.ok(event.data.oldState.deposited);
+        assert.ok(event.data.newState.deposited);
+    });
+
+    it("buyer cannot release a milestone twice", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.releaseMilestone(0)
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "MilestoneReleased");
+    });
+
+    it("a dispute freezes the remaining milestones", async () => {
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.releaseMilestone(1)
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("referee can refund only the unreleased milestones", async () => {
+        await program.methods.refund()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                refereeTokenAccount: referee.tokenAccounts["USDC"],
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 170);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 30);
+    });
+
+});
+
+describe("Seahorse Escrow - Rule Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, referee)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("buyer share cannot exceed 10000 bps", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.rule(10001)
+                .accounts({
+                    referee: referee.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                    refereeTokenAccount: referee.tokenAccounts["USDC"],
+                })
+                .signers([referee])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidRuling");
+    });
+
+    it("referee can split the vault between buyer and seller", async () => {
+        await program.methods.rule(2500)
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                refereeTokenAccount: referee.tokenAccounts["USDC"],
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 125);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 75);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.ruling == 2500);
+        assert.ok(order.state.resolved);
+    });
+
+});
+
+describe("Seahorse Escrow - Mutual Cancel Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("the buyer of the order must sign a mutual cancel", async () => {
+        let success = false;
+        let code: string;
+        const hacker = await SimpleUser.generate(provider.connection);
+
+        try {
+            await program.methods.mutualCancel()
+                .accounts({
+                    buyer: hacker.publicKey,
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([hacker, seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotBuyer");
+    });
+
+    it("buyer and seller can cancel a deposited order together", async () => {
+        await program.methods.mutualCancel()
+            .accounts({
+                buyer: buyer.publicKey,
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([buyer, seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount } = await buyer.balance("USDC");
+        assert.ok(amount == 200);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.refunded);
+    });
+
+});
+
+describe("Seahorse Escrow - Seller Refund Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("seller cannot refund more than the deposit", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sellerRefund(new BN(101 * Math.pow(10, 9)))
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "RefundTooLarge");
+    });
+
+    it("seller cannot refund nothing", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sellerRefund(new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidRefund");
+    });
+
+    it("seller can refund part of the deposit and keep the rest", async () => {
+        await program.methods.sellerRefund(new BN(40 * Math.pow(10, 9)))
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 140);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 60);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.refunded);
+    });
+
+    it("seller cannot refund part of a disputed deposit", async () => {
+        const [disputedAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(1)]
+        );
+
+        const [disputedVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", disputedAddress]
+        );
+
+        await program.methods.initOrder(referee.publicKey, new BN(100 * Math.pow(10, 9)), 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: disputedAddress,
+                sellerProfile: profileAddress,
+                vault: disputedVaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: disputedAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: disputedVaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: disputedAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sellerRefund(new BN(40 * Math.pow(10, 9)))
+                .accounts({
+                    seller: seller.publicKey,
+                    order: disputedAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: disputedVaultAddress,
+                    bondVault: null,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+});
+
+describe("Seahorse Escrow - Config Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let referee: SimpleUser;
+    let newAdmin: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+        newAdmin = await SimpleUser.generate(provider.connection);
+
+        await minter.mint("USDC")
+            .transfer("USDC", 0, seller)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+    });
+
+    it("only the admin can pause", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.setPaused(true)
+                .accounts({
+                    admin: seller.publicKey,
+                    config: configAddress,
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotAdmin");
+    });
+
+    it("sellers cannot open orders while paused", async () => {
+        await program.methods.setPaused(true)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            const amount = new BN(100 * Math.pow(10, 9));
+            await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    config: configAddress,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: null
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        await program.methods.setPaused(false)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        assert.ok(success == false);
+        assert.ok(code == "Paused");
+    });
+
+    it("only the proposed admin can accept", async () => {
+        await program.methods.proposeAdmin(newAdmin.publicKey)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.acceptAdmin()
+                .accounts({
+                    newAdmin: seller.publicKey,
+                    config: configAddress,
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotPendingAdmin");
+    });
+
+    it("admin can be handed over in two steps", async () => {
+        await program.methods.acceptAdmin()
+            .accounts({
+                newAdmin: newAdmin.publicKey,
+                config: configAddress,
+            })
+            .signers([newAdmin])
+            .rpc({ skipPreflight: true });
+
+        let config = await program.account.config.fetch(configAddress);
+        assert.ok(config.admin.toBase58() == newAdmin.publicKey.toBase58());
+        assert.ok(config.pendingAdmin == null);
+
+        // hand it back so later routes can still use the provider wallet
+        await program.methods.proposeAdmin(provider.wallet.publicKey)
+            .accounts({
+                admin: newAdmin.publicKey,
+                config: configAddress,
+            })
+            .signers([newAdmin])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.acceptAdmin()
+            .accounts({
+                newAdmin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        config = await program.account.config.fetch(configAddress);
+        assert.ok(config.admin.toBase58() == provider.wallet.publicKey.toBase58());
+    });
+
+
+    it("only the admin can set the treasury", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.setTreasury(seller.publicKey)
+                .accounts({
+                    admin: seller.publicKey,
+                    config: configAddress,
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NotAdmin");
+    });
+
+    it("admin can move the treasury", async () => {
+        await program.methods.setTreasury(newAdmin.publicKey)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        let config = await program.account.config.fetch(configAddress);
+        assert.ok(config.treasury.toBase58() == newAdmin.publicKey.toBase58());
+
+        // move it back so later routes still pay the shared treasury
+        await program.methods.setTreasury(treasury.publicKey)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        config = await program.account.config.fetch(configAddress);
+        assert.ok(config.treasury.toBase58() == treasury.publicKey.toBase58());
+    });
+
+});
+
+describe("Seahorse Escrow - Protocol Fee Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+    });
+
+    it("order snapshots the protocol fee at creation", async () => {
+        await program.methods.setFee(250)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                config: configAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        // a later change does not reach the live order
+        await program.methods.setFee(0)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.protocolFeeBps == 250);
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+    });
+
+    it("protocol fee must go to the treasury", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.release()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: buyer.tokenAccounts["USDC"],
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+            
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "ConstraintTokenOwner");
+    });
+
+    it("release pays the protocol fee to the treasury", async () => {
+        await program.methods.release()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: null,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const { amount: fee } = await treasury.balance("USDC");
+        assert.ok(fee == 2.5);
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 97.5);
+    });
+
+    it("native orders pay the protocol fee in lamports", async () => {
+        const [nativeAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(1)]
+        );
+
+        await program.methods.setFee(250)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        const amount = new BN(web3.LAMPORTS_PER_SOL / 2);
+        await program.methods.initNativeOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null)
+            .accounts({
+                seller: seller.publicKey,
+                order: nativeAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.setFee(0)
+            .accounts({
+                admin: provider.wallet.publicKey,
+                config: configAddress,
+            })
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: nativeAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const treasuryBefore = await provider.connection.getBalance(treasury.publicKey);
+        const sellerBefore = await provider.connection.getBalance(seller.publicKey);
+
+        await program.methods.releaseNative()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: nativeAddress,
+                sellerProfile: profileAddress,
+                seller: seller.publicKey,
+                treasury: treasury.publicKey,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const treasuryAfter = await provider.connection.getBalance(treasury.publicKey);
+        assert.ok(treasuryAfter - treasuryBefore == amount.toNumber() * 0.025);
+
+        const sellerAfter = await provider.connection.getBalance(seller.publicKey);
+        assert.ok(sellerAfter - sellerBefore == amount.toNumber() * 0.975);
+    });
+
+});
+
+describe("Seahorse Escrow - Bond Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+    let referee: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+    let bondVaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+        referee = await SimpleUser.generate(provider.connection); 
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 100, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+    });
+
+    it("seller posts a bond when creating an order", async () => {
+        const amount = new BN(100 * Math.pow(10, 9));
+        const bond = new BN(50 * Math.pow(10, 9));
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], bond)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.bond.toNumber() == 50 * Math.pow(10, 9));
+
+        const bondVault = await getAccount(provider.connection, bondVaultAddress);
+        assert.ok(Number(bondVault.amount) == 50 * Math.pow(10, 9));
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 50);
+    });
+
+    it("a refund by the referee forfeits the bond to the buyer", async () => {
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.refund()
+            .accounts({
+                referee: referee.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                refereeTokenAccount: seller.tokenAccounts["USDC"]
+            })
+            .signers([referee])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 250);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 50);
+    });
+
+    it("seller gets the bond back when cancelling a pending order", async () => {
+        orderId = 1;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        const bond = new BN(50 * Math.pow(10, 9));
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], bond)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.cancelOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 50);
+
+        const bondVault = await provider.connection.getAccountInfo(bondVaultAddress);
+        assert.ok(bondVault == null);
+    });
+
+
+    it("an order without a bond does not create a bond vault", async () => {
+        orderId = 2;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+
+        let success = false;
+        let code: string;
+        try {
+            await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+                .accounts({
+                    seller: seller.publicKey,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    mint: minter.tokens["USDC"].mint,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    vault: vaultAddress,
+                    bondVault: bondVaultAddress
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+            success = true;
+        } catch (err) {
+            code = err.error.errorCode.code;
+        }
+        assert.ok(success == false);
+        assert.ok(code == "WrongBondVault");
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], new BN(0))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: null
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const bondVault = await provider.connection.getAccountInfo(bondVaultAddress);
+        assert.ok(bondVault == null);
+    });
+
+    it("a partial seller refund forfeits the refunded share of the bond", async () => {
+        orderId = 3;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        const bond = new BN(40 * Math.pow(10, 9));
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], bond)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.sellerRefund(new BN(25 * Math.pow(10, 9)))
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 185);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 115);
+    });
+
+    it("a seller refund of a disputed order forfeits the bond to the buyer", async () => {
+        orderId = 4;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        const bond = new BN(40 * Math.pow(10, 9));
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], bond)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.deposit()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                vault: vaultAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.dispute()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.sellerRefund(null)
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: buyerAmount } = await buyer.balance("USDC");
+        assert.ok(buyerAmount == 225);
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 75);
+    });
+
+
+    it("tokens sent to the bond vault do not block closing a finished order", async () => {
+        await transfer(
+            provider.connection,
+            buyer,
+            buyer.tokenAccounts["USDC"],
+            bondVaultAddress,
+            buyer,
+            Math.pow(10, 9)
+        );
+
+        await program.methods.closeOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 76);
+
+        const bondVault = await provider.connection.getAccountInfo(bondVaultAddress);
+        assert.ok(bondVault == null);
+    });
+
+    it("tokens sent to the bond vault go back with the bond on cancel", async () => {
+        orderId = 5;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [bondVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["bond", orderAddress]
+        );
+
+        const amount = new BN(100 * Math.pow(10, 9));
+        const bond = new BN(10 * Math.pow(10, 9));
+
+        await program.methods.initOrder(referee.publicKey, amount, 0, RELEASE_WINDOW, DISPUTE_WINDOW, { refund: {} }, null, null, [], bond)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await transfer(
+            provider.connection,
+            buyer,
+            buyer.tokenAccounts["USDC"],
+            bondVaultAddress,
+            buyer,
+            Math.pow(10, 9)
+        );
+
+        await program.methods.cancelOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                bondVault: bondVaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount: sellerAmount } = await seller.balance("USDC");
+        assert.ok(sellerAmount == 77);
+
+        const bondVault = await provider.connection.getAccountInfo(bondVaultAddress);
+        assert.ok(bondVault == null);
+    });
+
+});
+
+describe("Seahorse Escrow - Swap Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let buyer: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+    let itemVaultAddress: web3.PublicKey;
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        buyer = await SimpleUser.generate(provider.connection);
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, buyer)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        await minter.mint("ITEM")
+            .transfer("ITEM", 2, seller)
+            .transfer("ITEM", 0, buyer)
+            .commit();
+
+        orderId = 0;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [itemVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["item", orderAddress]
+        );
+    });
+
+    it("seller escrows the item when creating a swap order", async () => {
+        const amount = new BN(100 * Math.pow(10, 9));
+        const itemAmount = new BN(Math.pow(10, 9));
+
+        await program.methods.initSwapOrder(amount, itemAmount, null)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                sellerItemAccount: seller.tokenAccounts["ITEM"],
+                itemMint: minter.tokens["ITEM"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                itemTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.mode.swap);
+        assert.ok(order.itemMint.toBase58() == minter.tokens["ITEM"].mint.toBase58());
+        assert.ok(order.itemVault.toBase58() == itemVaultAddress.toBase58());
+        assert.ok(order.itemAmount.toNumber() == Math.pow(10, 9));
+
+        const { amount: itemAmountLeft } = await seller.balance("ITEM");
+        assert.ok(itemAmountLeft == 1);
+    });
+
+    it("buyer cannot deposit to a swap order without swapping", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.deposit()
+                .accounts({
+                    buyer: buyer.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                    vault: vaultAddress,
+                })
+                .signers([buyer])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPaymentMode");
+    });
+
+    it("buyer deposit pays the seller and delivers the item at once", async () => {
+        const tx = await program.methods.depositSwap()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                itemMint: minter.tokens["ITEM"].mint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                buyerItemAccount: buyer.tokenAccounts["ITEM"],
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                itemTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const events = await fetchEvents(tx);
+        assert.ok(events[events.length - 1].data.newState.settled);
+
+        const { amount: item } = await buyer.balance("ITEM");
+        assert.ok(item == 1);
+
+        const { amount: payment } = await seller.balance("USDC");
+        assert.ok(payment == 100);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.settled);
+    });
+
+    it("seller can close a settled swap order", async () => {
+        await program.methods.closeSwapOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                itemMint: minter.tokens["ITEM"].mint,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                sellerItemAccount: seller.tokenAccounts["ITEM"],
+                itemTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+
+        const itemVault = await provider.connection.getAccountInfo(itemVaultAddress);
+        assert.ok(itemVault == null);
+    });
+
+
+    it("item can live on a different token program than the payment", async () => {
+        orderId = 1;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [itemVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["item", orderAddress]
+        );
+
+        // a Token-2022 item paid for in a classic SPL token
+        const itemMint = await createMint(
+            provider.connection, seller, seller.publicKey, null, 0, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+        const sellerItemAccount = await createAccount(
+            provider.connection, seller, itemMint, seller.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+        const buyerItemAccount = await createAccount(
+            provider.connection, buyer, itemMint, buyer.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+        );
+
+        await mintTo(
+            provider.connection, seller, itemMint, sellerItemAccount, seller, 1, [], undefined, TOKEN_2022_PROGRAM_ID
+        );
+
+        await program.methods.initSwapOrder(new BN(50 * Math.pow(10, 9)), new BN(1), null)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                sellerItemAccount,
+                itemMint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                itemTokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        await program.methods.depositSwap()
+            .accounts({
+                buyer: buyer.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                itemMint,
+                buyerTokenAccount: buyer.tokenAccounts["USDC"],
+                buyerItemAccount,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                itemTokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([buyer])
+            .rpc({ skipPreflight: true });
+
+        const item = await getAccount(provider.connection, buyerItemAccount, undefined, TOKEN_2022_PROGRAM_ID);
+        assert.ok(Number(item.amount) == 1);
+
+        const { amount: payment } = await seller.balance("USDC");
+        assert.ok(payment == 150);
+
+        await program.methods.closeSwapOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                itemMint,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                sellerItemAccount,
+                itemTokenProgram: TOKEN_2022_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const itemVault = await provider.connection.getAccountInfo(itemVaultAddress);
+        assert.ok(itemVault == null);
+    });
+
+
+    it("seller cannot cancel a swap order as a plain order", async () => {
+        orderId = 2;
+
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        [itemVaultAddress,] = findProgramAddress(
+            program.programId,
+            ["item", orderAddress]
+        );
+
+        await program.methods.initSwapOrder(new BN(100 * Math.pow(10, 9)), new BN(Math.pow(10, 9)), null)
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                sellerItemAccount: seller.tokenAccounts["ITEM"],
+                itemMint: minter.tokens["ITEM"].mint,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                itemTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.cancelOrder()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPaymentMode");
+    });
+
+    it("tokens sent to the item vault do not block closing a pending swap order", async () => {
+        // one base unit sent straight to the item vault
+        await transfer(
+            provider.connection,
+            buyer,
+            buyer.tokenAccounts["ITEM"],
+            itemVaultAddress,
+            buyer,
+            1
+        );
+
+        await program.methods.closeSwapOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                itemMint: minter.tokens["ITEM"].mint,
+                vault: vaultAddress,
+                itemVault: itemVaultAddress,
+                sellerItemAccount: seller.tokenAccounts["ITEM"],
+                itemTokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const item = await getAccount(provider.connection, seller.tokenAccounts["ITEM"]);
+        assert.ok(Number(item.amount) == Math.pow(10, 9) + 1);
+
+        const itemVault = await provider.connection.getAccountInfo(itemVaultAddress);
+        assert.ok(itemVault == null);
+    });
+
+});
+
+describe("Seahorse Escrow - Crowdfund Route", async () => {
+
+    let minter: SimpleUser;
+    let seller: SimpleUser;
+    let alice: SimpleUser;
+    let bob: SimpleUser;
+
+    let orderId: number;
+    let orderAddress: web3.PublicKey;
+    let profileAddress: web3.PublicKey;
+    let vaultAddress: web3.PublicKey;
+
+    function contributionAddress(contributor: SimpleUser) {
+        const [address, ] = findProgramAddress(
+            program.programId,
+            ["contribution", orderAddress, contributor.publicKey]
+        );
+        return address;
+    }
+
+    async function contribute(contributor: SimpleUser, amount: number) {
+        await program.methods.contribute(new BN(amount * Math.pow(10, 9)))
+            .accounts({
+                contributor: contributor.publicKey,
+                order: orderAddress,
+                mint: minter.tokens["USDC"].mint,
+                contributorTokenAccount: contributor.tokenAccounts["USDC"],
+                vault: vaultAddress,
+                contribution: contributionAddress(contributor),
+            })
+            .signers([contributor])
+            .rpc({ skipPreflight: true });
+    }
+
+    async function initCrowdfundOrder(target: number, fundingDeadline: number) {
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(orderId)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        await program.methods.initCrowdfundOrder(new BN(target * Math.pow(10, 9)), new BN(fundingDeadline))
+            .accounts({
+                seller: seller.publicKey,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                mint: minter.tokens["USDC"].mint,
+                sellerProfile: profileAddress,
+                order: orderAddress,
+                vault: vaultAddress
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+    }
+
+    before("", async () => {
+
+        minter = await SimpleUser.generate(provider.connection);
+        seller = await SimpleUser.generate(provider.connection);
+        alice = await SimpleUser.generate(provider.connection);
+        bob = await SimpleUser.generate(provider.connection);
+
+        await minter.mint("USDC")
+            .transfer("USDC", 200, alice)
+            .transfer("USDC", 100, bob)
+            .transfer("USDC", 0, seller)
+            .transfer("USDC", 0, treasury)
+            .commit();
+
+        orderId = 0;
+
+        [profileAddress, ] = findProgramAddress(
+            program.programId,
+            ["seller", seller.publicKey]
+        );
+    });
+
+    it("seller can open a crowdfund order with a target and deadline", async () => {
+        const fundingDeadline = await clockTime() + 600;
+        await initCrowdfundOrder(150, fundingDeadline);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.mode.crowdfund);
+        assert.ok(order.amount.toNumber() == 150 * Math.pow(10, 9));
+        assert.ok(order.fundingDeadline.toNumber() == fundingDeadline);
+        assert.ok(order.state.pending);
+    });
+
+    it("contributors get a receipt for what they put in", async () => {
+        await contribute(alice, 100);
+
+        const contribution = await program.account.contribution.fetch(contributionAddress(alice));
+        assert.ok(contribution.contributor.toBase58() == alice.publicKey.toBase58());
+        assert.ok(contribution.amount.toNumber() == 100 * Math.pow(10, 9));
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.depositedAmount.toNumber() == 100 * Math.pow(10, 9));
+        assert.ok(order.state.pending);
+    });
+
+    it("seller cannot release before the target is met", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.releaseCrowdfund()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("contributors cannot reclaim before the deadline", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.reclaimContribution()
+                .accounts({
+                    contributor: alice.publicKey,
+                    order: orderAddress,
+                    contribution: contributionAddress(alice),
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    contributorTokenAccount: alice.tokenAccounts["USDC"],
+                })
+                .signers([alice])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "FundingOpen");
+    });
+
+    it("seller cannot sweep contributions while funding is open", async () => {
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.sweepExcess()
+                .accounts({
+                    seller: seller.publicKey,
+                    order: orderAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                })
+                .signers([seller])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "NothingToSweep");
+
+        const vault = await getAccount(provider.connection, vaultAddress);
+        assert.ok(Number(vault.amount) == 100 * Math.pow(10, 9));
+    });
+
+    it("a funded crowdfund cannot be settled with claim_after_timeout", async () => {
+        await contribute(bob, 50);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.deposited);
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.claimAfterTimeout()
+                .accounts({
+                    caller: bob.publicKey,
+                    order: orderAddress,
+                    sellerProfile: profileAddress,
+                    mint: minter.tokens["USDC"].mint,
+                    vault: vaultAddress,
+                    bondVault: null,
+                    sellerTokenAccount: seller.tokenAccounts["USDC"],
+                    treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+                })
+                .signers([bob])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "WrongPaymentMode");
+    });
+
+    it("seller can release once the target is met", async () => {
+        await program.methods.releaseCrowdfund()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                sellerTokenAccount: seller.tokenAccounts["USDC"],
+                treasuryTokenAccount: treasury.tokenAccounts["USDC"],
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const { amount } = await seller.balance("USDC");
+        assert.ok(amount == 150);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.state.settled);
+    });
+
+    it("contributors can reclaim their share after an unmet deadline", async () => {
+        orderId = 1;
+        const fundingDeadline = await clockTime() + 10;
+        await initCrowdfundOrder(100, fundingDeadline);
+        await contribute(alice, 40);
+
+        let { amount } = await alice.balance("USDC");
+        assert.ok(amount == 60);
+
+        await waitForClock(fundingDeadline);
+
+        await program.methods.reclaimContribution()
+            .accounts({
+                contributor: alice.publicKey,
+                order: orderAddress,
+                contribution: contributionAddress(alice),
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+                contributorTokenAccount: alice.tokenAccounts["USDC"],
+            })
+            .signers([alice])
+            .rpc({ skipPreflight: true });
+
+        ({ amount } = await alice.balance("USDC"));
+        assert.ok(amount == 100);
+
+        const contribution = await program.account.contribution.fetchNullable(contributionAddress(alice));
+        assert.ok(contribution == null);
+
+        const order = await program.account.escrowOrder.fetch(orderAddress);
+        assert.ok(order.depositedAmount.toNumber() == 0);
+    });
+
+
+    it("seller can close a crowdfund order once every contribution is reclaimed", async () => {
+        await program.methods.closeCrowdfundOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+
+        const vault = await provider.connection.getAccountInfo(vaultAddress);
+        assert.ok(vault == null);
+    });
+
+    it("contributors cannot close their receipt while funding is open", async () => {
+        orderId = 2;
+        await initCrowdfundOrder(100, await clockTime() + 600);
+        await contribute(bob, 10);
+
+        let success = false;
+        let code: string;
+
+        try {
+            await program.methods.closeContribution()
+                .accounts({
+                    contributor: bob.publicKey,
+                    order: orderAddress,
+                    contribution: contributionAddress(bob),
+                })
+                .signers([bob])
+                .rpc({ skipPreflight: true });
+
+            success = true;
+        } catch(err) {
+            code = err.error.errorCode.code;
+        }
+
+        assert.ok(success == false);
+        assert.ok(code == "InvalidState");
+    });
+
+    it("contributors can close their receipt once the crowdfund settles", async () => {
+        [orderAddress, ] = findProgramAddress(
+            program.programId,
+            ["order", seller.publicKey, u64(0)]
+        );
+
+        [vaultAddress,] = findProgramAddress(
+            program.programId,
+            ["vault", orderAddress]
+        );
+
+        await program.methods.closeContribution()
+            .accounts({
+                contributor: alice.publicKey,
+                order: orderAddress,
+                contribution: contributionAddress(alice),
+            })
+            .signers([alice])
+            .rpc({ skipPreflight: true });
+
+        const contribution = await program.account.contribution.fetchNullable(contributionAddress(alice));
+        assert.ok(contribution == null);
+    });
+
+    it("seller can close a settled crowdfund order and receipts can still be closed after it", async () => {
+        await program.methods.closeCrowdfundOrder()
+            .accounts({
+                seller: seller.publicKey,
+                order: orderAddress,
+                sellerProfile: profileAddress,
+                mint: minter.tokens["USDC"].mint,
+                vault: vaultAddress,
+            })
+            .signers([seller])
+            .rpc({ skipPreflight: true });
+
+        const order = await program.account.escrowOrder.fetchNullable(orderAddress);
+        assert.ok(order == null);
+
+        await program.methods.closeContribution()
+            .accounts({
+                contributor: bob.publicKey,
+                order: orderAddress,
+                contribution: contributionAddress(bob),
+            })
+            .signers([bob])
+            .rpc({ skipPreflight: true });
+
+        const contribution = await program.account.contribution.fetchNullable(contributionAddress(bob));
+        assert.ok(contribution == null);
+    });
+
+});